@@ -0,0 +1,109 @@
+// Adoption of a bot started outside the app.
+//
+// A bot answering `/metrics` on a stopped profile's port was started by hand
+// (e.g. `pnpm run dev` in a terminal). Its PID is looked up from the listening
+// socket and from then on it is managed like a spawned bot: stop and restart
+// signal its process group, and status and health report its PID. It is not
+// ours, though, so it is left running when the app quits.
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use log::{error, info, warn};
+
+use crate::lifecycle::{self, BotPhase};
+use crate::process::BotProcess;
+use crate::profiles::BotProfile;
+use crate::{get_http_client, history, procfs, supervisor, watchdog, BotInstance};
+
+// Probe the profile's port and adopt the bot answering there, if any.
+// Returns whether a bot answered, whether or not it could be adopted.
+pub async fn detect(app: &AppHandle, bot: &Arc<BotInstance>, profile: &BotProfile) -> bool {
+    let client = get_http_client();
+    let answered = match client
+        .get(format!("{}/metrics", profile.base_url()))
+        .send()
+        .await
+    {
+        Ok(response) => response.status().is_success(),
+        Err(e) => {
+            error!("External bot check failed: {}", e);
+            return false;
+        }
+    };
+    info!("External bot check: is_running={}", answered);
+
+    if answered {
+        match adopt(app, bot, profile.port) {
+            Ok(pid) => info!("Adopted external bot [{}] with PID {}", bot.profile_id, pid),
+            Err(e) => warn!("External bot [{}] could not be adopted: {}", bot.profile_id, e),
+        }
+    }
+    answered
+}
+
+fn adopt(app: &AppHandle, bot: &Arc<BotInstance>, port: u16) -> Result<u32, String> {
+    let pid = procfs::listening_pid(port)
+        .ok_or_else(|| format!("No process found listening on port {}", port))?;
+
+    #[cfg(unix)]
+    let pgid = {
+        let pgid = crate::process::pgid_of(pid).ok_or_else(|| format!("Process {} exited", pid))?;
+        // Signalling our own group on stop would take the desktop app down too
+        if pgid == crate::process::own_pgid() {
+            return Err(format!("Process {} shares the desktop app's process group", pid));
+        }
+        pgid
+    };
+    #[cfg(not(unix))]
+    let pgid = pid;
+
+    attach(app, bot, pid, pgid, procfs::process_age(pid), true, None)?;
+    Ok(pid)
+}
+
+// Manage a running process we did not spawn in this session. `age` is how
+// long it has been running, when known, so uptime counts from its real start.
+// `external` marks a bot someone else started, as opposed to one of ours
+// reattached after a desktop restart, whose output is captured again by
+// `stderr_reader` and its stdout counterpart.
+pub fn attach(
+    app: &AppHandle,
+    bot: &Arc<BotInstance>,
+    pid: u32,
+    pgid: u32,
+    age: Option<Duration>,
+    external: bool,
+    stderr_reader: Option<JoinHandle<()>>,
+) -> Result<(), String> {
+    let mut process_guard = bot.process.lock().map_err(|e| e.to_string())?;
+    if process_guard.is_some() {
+        return Err("Bot is already managed".to_string());
+    }
+    lifecycle::transition(app, bot, BotPhase::Ready).map_err(|_| "Bot is not stopped".to_string())?;
+
+    *process_guard = Some(BotProcess::Adopted { pid, pgid, external });
+    let run_id = bot.run_id.fetch_add(1, Ordering::SeqCst) + 1;
+
+    let started = age
+        .and_then(|age| Instant::now().checked_sub(age))
+        .unwrap_or_else(Instant::now);
+    *bot.start_time.lock().map_err(|e| e.to_string())? = Some(started);
+    let started_at = chrono::Local::now()
+        - chrono::Duration::from_std(started.elapsed()).unwrap_or_else(|_| chrono::Duration::zero());
+    history::begin(bot, started_at, true);
+
+    // Nothing is captured from an external bot's output
+    if stderr_reader.is_none() {
+        if let Ok(mut tail) = bot.stderr_tail.lock() {
+            tail.clear();
+        }
+    }
+
+    drop(process_guard);
+    watchdog::spawn(app.clone(), bot.clone(), run_id);
+    supervisor::spawn_watcher(app.clone(), bot.clone(), run_id, stderr_reader);
+    Ok(())
+}
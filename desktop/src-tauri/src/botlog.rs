@@ -0,0 +1,329 @@
+// Bot output on disk.
+//
+// Bot lines are written to bot.log in the app log dir by this module rather
+// than by the log plugin, so the file can be rotated: once it grows past
+// `log_max_size_mb` or gets older than `log_max_age_hours` it is renamed to
+// bot-<timestamp>.log, gzipped if `log_compress` is set, and rotations beyond
+// the newest `log_retention` are deleted. The desktop app's own messages stay
+// with the plugin in desktop.log.
+//
+// Each launch also gets its own file under runs/, named by start time and the
+// run id of the history ledger, so one run's output can be looked at alone.
+
+use chrono::{DateTime, Local};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+use log::error;
+
+use crate::history::{self, RunRecord};
+use crate::logs::LogEntry;
+use crate::supervisor::SupervisorConfig;
+use crate::{profiles, BotState};
+
+const FILE_NAME: &str = "bot.log";
+const ROTATED_PREFIX: &str = "bot-";
+const RUNS_DIR: &str = "runs";
+
+struct Active {
+    file: File,
+    size: u64,
+    opened: SystemTime,
+}
+
+pub struct BotLog {
+    dir: PathBuf,
+    active: Mutex<Option<Active>>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct LogFile {
+    name: String,
+    path: String,
+    size_bytes: u64,
+    modified: Option<String>,
+    compressed: bool,
+}
+
+// Output of a single run
+pub struct RunLog {
+    file: Mutex<File>,
+}
+
+impl RunLog {
+    pub fn write(&self, entry: &LogEntry) {
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+        if let Err(e) = file.write_all(format_line(entry).as_bytes()) {
+            error!("Failed to write run log: {}", e);
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct RunLogInfo {
+    #[serde(flatten)]
+    run: RunRecord,
+    // None for adopted runs and runs from before per-run logs
+    log_file: Option<String>,
+    size_bytes: Option<u64>,
+}
+
+// Same layout the Logs tab parses: [timestamp][LEVEL] message
+fn format_line(entry: &LogEntry) -> String {
+    format!("[{}][{}] {}\n", entry.timestamp, entry.level.as_log(), entry.message)
+}
+
+fn is_rotated(name: &str) -> bool {
+    name.starts_with(ROTATED_PREFIX) && (name.ends_with(".log") || name.ends_with(".log.gz"))
+}
+
+// Write `path` to `path`.gz and remove the original
+fn compress(path: &Path) -> Result<(), String> {
+    let mut gz_name = path.as_os_str().to_owned();
+    gz_name.push(".gz");
+    let mut input = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let output = File::create(&gz_name).map_err(|e| format!("Failed to create compressed log: {}", e))?;
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    std::io::copy(&mut input, &mut encoder).map_err(|e| format!("Failed to compress log: {}", e))?;
+    encoder.finish().map_err(|e| format!("Failed to compress log: {}", e))?;
+    std::fs::remove_file(path).map_err(|e| format!("Failed to remove uncompressed log: {}", e))
+}
+
+impl BotLog {
+    pub fn new(dir: PathBuf) -> Self {
+        BotLog {
+            dir,
+            active: Mutex::new(None),
+        }
+    }
+
+    fn open(&self) -> Result<Active, String> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(FILE_NAME))
+            .map_err(|e| format!("Failed to open bot log: {}", e))?;
+        let meta = file.metadata().map_err(|e| format!("Failed to read bot log metadata: {}", e))?;
+        Ok(Active {
+            size: meta.len(),
+            // Without a birth time the age limit counts from now
+            opened: meta.created().unwrap_or_else(|_| SystemTime::now()),
+            file,
+        })
+    }
+
+    fn due(active: &Active, config: &SupervisorConfig) -> bool {
+        let too_big = config.log_max_size_mb > 0 && active.size >= config.log_max_size_mb * 1024 * 1024;
+        let too_old = config.log_max_age_hours > 0
+            && active.opened.elapsed().unwrap_or_default()
+                >= Duration::from_secs(config.log_max_age_hours * 3600);
+        active.size > 0 && (too_big || too_old)
+    }
+
+    // Rename bot.log out of the way and compress and prune in the background
+    fn rotate(&self, config: &SupervisorConfig) -> Result<(), String> {
+        let stamp = Local::now().format("%Y%m%d-%H%M%S-%3f");
+        let rotated = self.dir.join(format!("{}{}.log", ROTATED_PREFIX, stamp));
+        std::fs::rename(self.dir.join(FILE_NAME), &rotated)
+            .map_err(|e| format!("Failed to rotate bot log: {}", e))?;
+
+        let dir = self.dir.clone();
+        let (compress_rotated, retention) = (config.log_compress, config.log_retention);
+        thread::spawn(move || {
+            if compress_rotated {
+                if let Err(e) = compress(&rotated) {
+                    error!("{}", e);
+                }
+            }
+            prune(&dir, retention);
+        });
+        Ok(())
+    }
+
+    pub fn write(&self, entry: &LogEntry, config: &SupervisorConfig) {
+        let Ok(mut active) = self.active.lock() else {
+            return;
+        };
+        if active.as_ref().is_some_and(|current| Self::due(current, config)) {
+            // Close our handle before the rename
+            *active = None;
+            if let Err(e) = self.rotate(config) {
+                error!("{}", e);
+            }
+        }
+        if active.is_none() {
+            match self.open() {
+                Ok(opened) => *active = Some(opened),
+                Err(e) => {
+                    error!("{}", e);
+                    return;
+                }
+            }
+        }
+        let Some(current) = active.as_mut() else {
+            return;
+        };
+
+        let line = format_line(entry);
+        match current.file.write_all(line.as_bytes()) {
+            Ok(()) => current.size += line.len() as u64,
+            Err(e) => {
+                error!("Failed to write bot log: {}", e);
+                // Reopened on the next line
+                *active = None;
+            }
+        }
+    }
+
+    // Create the log of run `run_id` and drop the oldest beyond `retention`
+    pub fn open_run(&self, run_id: &str, started_at: DateTime<Local>, retention: usize) -> Option<Arc<RunLog>> {
+        let dir = self.dir.join(RUNS_DIR);
+        let name = format!("{}_{}.log", started_at.format("%Y%m%d-%H%M%S"), run_id);
+        let opened = std::fs::create_dir_all(&dir).and_then(|_| {
+            OpenOptions::new().create(true).append(true).open(dir.join(name))
+        });
+        let file = match opened {
+            Ok(file) => file,
+            Err(e) => {
+                error!("Failed to create run log: {}", e);
+                return None;
+            }
+        };
+
+        let mut logs = run_logs(&dir);
+        logs.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+        for path in logs.into_iter().skip(retention.max(1)) {
+            if let Err(e) = std::fs::remove_file(&path) {
+                error!("Failed to remove old run log {}: {}", path.display(), e);
+            }
+        }
+        Some(Arc::new(RunLog { file: Mutex::new(file) }))
+    }
+
+    fn run_log_path(&self, run_id: &str) -> Option<PathBuf> {
+        let suffix = format!("_{}.log", run_id);
+        run_logs(&self.dir.join(RUNS_DIR))
+            .into_iter()
+            .find(|path| path.file_name().is_some_and(|name| name.to_string_lossy().ends_with(&suffix)))
+    }
+}
+
+// Rotated logs in `dir`, newest first; the timestamp in the name sorts
+fn rotated_files(dir: &Path) -> Vec<(String, PathBuf, std::fs::Metadata)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let meta = entry.metadata().ok()?;
+            (is_rotated(&name) && meta.is_file()).then(|| (name, entry.path(), meta))
+        })
+        .collect();
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    files
+}
+
+fn run_logs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .collect()
+}
+
+// Delete rotations beyond the newest `retention`
+fn prune(dir: &Path, retention: usize) {
+    for (_, path, _) in rotated_files(dir).into_iter().skip(retention) {
+        if let Err(e) = std::fs::remove_file(&path) {
+            error!("Failed to remove old bot log {}: {}", path.display(), e);
+        }
+    }
+}
+
+// Rotated bot logs, newest first
+#[tauri::command]
+pub fn list_log_files(log: tauri::State<BotLog>) -> Vec<LogFile> {
+    rotated_files(&log.dir)
+        .into_iter()
+        .map(|(name, path, meta)| LogFile {
+            compressed: name.ends_with(".gz"),
+            name,
+            path: path.to_string_lossy().to_string(),
+            size_bytes: meta.len(),
+            modified: meta
+                .modified()
+                .ok()
+                .map(|at| DateTime::<Local>::from(at).to_rfc3339()),
+        })
+        .collect()
+}
+
+// Contents of a rotated bot log by name, decompressed when gzipped
+#[tauri::command]
+pub fn read_log_file(log: tauri::State<BotLog>, name: String) -> Result<String, String> {
+    // A bare file name only, so nothing outside the log dir can be read
+    if !is_rotated(&name) || Path::new(&name).file_name() != Some(std::ffi::OsStr::new(&name)) {
+        return Err(format!("Not a rotated bot log: {}", name));
+    }
+    let file = File::open(log.dir.join(&name)).map_err(|e| format!("Failed to open {}: {}", name, e))?;
+
+    let mut reader: Box<dyn Read> = if name.ends_with(".gz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    let mut content = String::new();
+    reader
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(content)
+}
+
+// Runs of a profile with their log files, newest first
+#[tauri::command]
+pub fn list_run_logs(
+    state: tauri::State<BotState>,
+    log: tauri::State<BotLog>,
+    profile_id: Option<String>,
+) -> Vec<RunLogInfo> {
+    let mut runs = history::runs_of(&state, &profiles::resolve_id(profile_id));
+    runs.sort_by(|a, b| b.started().cmp(&a.started()));
+    runs.into_iter()
+        .map(|run| {
+            let path = log.run_log_path(&run.id);
+            RunLogInfo {
+                size_bytes: path.as_ref().and_then(|path| std::fs::metadata(path).ok()).map(|meta| meta.len()),
+                log_file: path.map(|path| path.to_string_lossy().to_string()),
+                run,
+            }
+        })
+        .collect()
+}
+
+// Open a run's log in the default app for text files
+#[tauri::command]
+pub fn open_run_log(app: AppHandle, log: tauri::State<BotLog>, run_id: String) -> Result<(), String> {
+    let path = log
+        .run_log_path(&run_id)
+        .ok_or_else(|| format!("No log for run {}", run_id))?;
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open run log: {}", e))
+}
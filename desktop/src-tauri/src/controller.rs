@@ -0,0 +1,203 @@
+// Single control path for the bot processes.
+//
+// Commands, tray and menu items, the global shortcut and the supervisor all
+// send requests to the profile's async actor. It runs them one at a time and
+// does the blocking parts (reading the secret store on start, waiting out the
+// stop grace period) on the blocking pool.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::{self, Receiver, Sender};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::oneshot;
+
+use crate::adopt;
+use crate::history::StopReason;
+use crate::lifecycle::{self, BotPhase};
+use crate::profiles::{self, DEFAULT_PROFILE};
+use crate::{
+    send_notification, start_bot_internal, stop_bot_internal, update_tray_status, BotState, Launch,
+    StopReport,
+};
+
+// Pause between stop and start on restart
+const RESTART_DELAY: Duration = Duration::from_millis(300);
+
+enum Request {
+    Start {
+        reply: oneshot::Sender<Result<Launch, String>>,
+    },
+    Stop {
+        reason: StopReason,
+        reply: oneshot::Sender<Result<StopReport, String>>,
+    },
+    Restart {
+        reason: StopReason,
+        reply: oneshot::Sender<Result<Launch, String>>,
+    },
+}
+
+// One actor per profile, spawned on first use
+pub struct BotController {
+    app: AppHandle,
+    actors: Mutex<HashMap<String, Sender<Request>>>,
+}
+
+impl BotController {
+    pub fn new(app: AppHandle) -> Self {
+        Self {
+            app,
+            actors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn sender(&self, profile_id: &str) -> Result<Sender<Request>, String> {
+        let mut actors = self.actors.lock().map_err(|e| e.to_string())?;
+        let tx = actors.entry(profile_id.to_string()).or_insert_with(|| {
+            let (tx, rx) = async_runtime::channel(16);
+            async_runtime::spawn(run(self.app.clone(), profile_id.to_string(), rx));
+            tx
+        });
+        Ok(tx.clone())
+    }
+
+    async fn request<T>(
+        &self,
+        profile_id: &str,
+        make: impl FnOnce(oneshot::Sender<T>) -> Request,
+    ) -> Result<T, String> {
+        let (reply, response) = oneshot::channel();
+        self.sender(profile_id)?
+            .send(make(reply))
+            .await
+            .map_err(|_| "Bot controller is not running".to_string())?;
+        response
+            .await
+            .map_err(|_| "Bot controller dropped the request".to_string())
+    }
+
+    pub async fn start(&self, profile_id: &str) -> Result<Launch, String> {
+        self.request(profile_id, |reply| Request::Start { reply }).await?
+    }
+
+    pub async fn stop(&self, profile_id: &str, reason: StopReason) -> Result<StopReport, String> {
+        self.request(profile_id, |reply| Request::Stop { reason, reply }).await?
+    }
+
+    pub async fn restart(&self, profile_id: &str, reason: StopReason) -> Result<Launch, String> {
+        self.request(profile_id, |reply| Request::Restart { reason, reply }).await?
+    }
+}
+
+async fn run(app: AppHandle, profile_id: String, mut rx: Receiver<Request>) {
+    while let Some(request) = rx.recv().await {
+        match request {
+            Request::Start { reply } => {
+                let _ = reply.send(start(&app, &profile_id).await);
+            }
+            Request::Stop { reason, reply } => {
+                let _ = reply.send(stop(&app, &profile_id, reason).await);
+            }
+            Request::Restart { reason, reply } => {
+                let _ = stop(&app, &profile_id, reason).await;
+                tokio::time::sleep(RESTART_DELAY).await;
+                let _ = reply.send(start(&app, &profile_id).await);
+            }
+        }
+    }
+}
+
+// Profiles are re-read on every start so edits apply to the next launch.
+// Secrets come from the system store (a `secret-tool` call, or a keychain read
+// that may prompt), so the launch runs on the blocking pool.
+async fn start(app: &AppHandle, profile_id: &str) -> Result<Launch, String> {
+    let profile = profiles::get(profile_id)?;
+    let app = app.clone();
+    let bot = app.state::<BotState>().instance(profile_id);
+    async_runtime::spawn_blocking(move || start_bot_internal(app, bot, &profile))
+        .await
+        .map_err(|e| format!("Start task failed: {}", e))?
+}
+
+// Stopping waits out the grace period, so it runs on the blocking pool
+async fn stop(app: &AppHandle, profile_id: &str, reason: StopReason) -> Result<StopReport, String> {
+    let app = app.clone();
+    let bot = app.state::<BotState>().instance(profile_id);
+    // A bot started outside the app can be stopped once it is adopted
+    if lifecycle::current(&bot) == BotPhase::Stopped {
+        if let Ok(profile) = profiles::get(profile_id) {
+            adopt::detect(&app, &bot, &profile).await;
+        }
+    }
+    async_runtime::spawn_blocking(move || stop_bot_internal(&app, &bot, reason))
+        .await
+        .map_err(|e| format!("Stop task failed: {}", e))?
+}
+
+#[derive(Clone, Copy)]
+pub enum BotAction {
+    Start,
+    Stop,
+    Restart,
+    // Stop if running, start otherwise
+    Toggle,
+}
+
+// Fire-and-forget entry point for tray, menu and shortcut handlers; `source`
+// is recorded as the stop reason. The outcome is reported through
+// `bot-status` / `bot-error` events.
+pub fn dispatch(app: &AppHandle, profile_id: &str, action: BotAction, source: StopReason, notify: bool) {
+    let app = app.clone();
+    let profile_id = profile_id.to_string();
+    async_runtime::spawn(async move {
+        let controller: State<BotController> = app.state();
+        let action = match action {
+            BotAction::Toggle
+                if lifecycle::current(&app.state::<BotState>().instance(&profile_id)).is_running() =>
+            {
+                BotAction::Stop
+            }
+            BotAction::Toggle => BotAction::Start,
+            action => action,
+        };
+        let title = if profile_id == DEFAULT_PROFILE {
+            "ChatCode Bot".to_string()
+        } else {
+            format!("ChatCode Bot ({})", profile_id)
+        };
+
+        let result = match action {
+            BotAction::Stop => controller.stop(&profile_id, source).await.map(|report| {
+                if notify {
+                    send_notification(&app, &title, "Bot stopped");
+                }
+                update_tray_status(&app, false, None);
+                report.message()
+            }),
+            BotAction::Restart => controller.restart(&profile_id, source).await.map(|launch| {
+                if notify {
+                    send_notification(&app, &title, "Bot restarted successfully");
+                }
+                update_tray_status(&app, true, None);
+                launch.message()
+            }),
+            BotAction::Start | BotAction::Toggle => controller.start(&profile_id).await.map(|launch| {
+                if notify {
+                    send_notification(&app, &title, "Bot started successfully");
+                }
+                update_tray_status(&app, true, None);
+                launch.message()
+            }),
+        };
+
+        match result {
+            Ok(msg) => {
+                let _ = app.emit("bot-status", msg);
+            }
+            Err(e) => {
+                let _ = app.emit("bot-error", e);
+            }
+        }
+    });
+}
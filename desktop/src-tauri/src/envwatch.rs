@@ -0,0 +1,251 @@
+// Reacting to configuration changes of a running bot.
+//
+// The bot reads its .env once at startup, so edits only apply after a restart.
+// A polling thread compares the modification time of each running profile's
+// .env (and src/ in dev mode, when enabled) and, once changes settle, either
+// asks the UI to offer a restart or restarts the bot through the controller.
+// Writes made by `save_config` are recorded so they can be told apart from
+// edits made outside the app.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State};
+use log::{error, info};
+
+use crate::controller::BotController;
+use crate::history::StopReason;
+use crate::launch::{self, LaunchMode};
+use crate::lifecycle::{self, BotPhase};
+use crate::{profiles, send_notification, BotState};
+
+// How often the files are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// What happens when a running bot's configuration changes
+#[derive(Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvReload {
+    Off,
+    // Tell the UI, which offers a restart
+    #[default]
+    Prompt,
+    // Restart once changes have settled
+    Auto,
+}
+
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeSource {
+    // Written by `save_config`
+    App,
+    External,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ConfigChanged {
+    profile_id: String,
+    source: ChangeSource,
+    // ".env" and/or "src/"
+    changed: Vec<String>,
+    // Whether the bot is being restarted automatically
+    restarting: bool,
+}
+
+// .env files written by the app, with the modification time they got
+#[derive(Default)]
+pub struct EnvWatcher {
+    own_writes: Mutex<HashMap<PathBuf, SystemTime>>,
+}
+
+impl EnvWatcher {
+    // Call right after the app writes `path`
+    pub fn note_write(&self, path: &Path) {
+        let Ok(modified) = std::fs::metadata(path).and_then(|meta| meta.modified()) else {
+            return;
+        };
+        if let Ok(mut writes) = self.own_writes.lock() {
+            writes.insert(path.to_path_buf(), modified);
+        }
+    }
+
+    fn is_own_write(&self, path: &Path, modified: Option<SystemTime>) -> bool {
+        let Ok(writes) = self.own_writes.lock() else {
+            return false;
+        };
+        modified.is_some() && writes.get(path).copied() == modified
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct Fingerprint {
+    env_modified: Option<SystemTime>,
+    env_len: Option<u64>,
+    // Newest modification under src/, when it is watched
+    src_modified: Option<SystemTime>,
+}
+
+// Newest modification time of any file below `dir`
+fn latest_modified(dir: &Path) -> Option<SystemTime> {
+    let mut latest = None;
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let modified = if meta.is_dir() {
+            latest_modified(&entry.path())
+        } else {
+            meta.modified().ok()
+        };
+        latest = latest.max(modified);
+    }
+    latest
+}
+
+fn fingerprint(project_path: &str, watch_src: bool) -> Fingerprint {
+    let env = std::fs::metadata(Path::new(project_path).join(".env")).ok();
+    Fingerprint {
+        env_modified: env.as_ref().and_then(|meta| meta.modified().ok()),
+        env_len: env.as_ref().map(|meta| meta.len()),
+        src_modified: watch_src
+            .then(|| latest_modified(&Path::new(project_path).join("src")))
+            .flatten(),
+    }
+}
+
+struct Pending {
+    last_change: Instant,
+    source: ChangeSource,
+    changed: Vec<String>,
+}
+
+// Watch state of one running profile
+struct Watch {
+    run_id: u64,
+    fingerprint: Fingerprint,
+    pending: Option<Pending>,
+}
+
+fn react(app: &AppHandle, profile_id: &str, pending: Pending, mode: EnvReload) {
+    let restarting = mode == EnvReload::Auto;
+    info!(
+        "Bot [{}] configuration changed ({}, {:?}), restart: {}",
+        profile_id,
+        pending.changed.join(", "),
+        pending.source,
+        restarting
+    );
+    let _ = app.emit("bot-config-changed", ConfigChanged {
+        profile_id: profile_id.to_string(),
+        source: pending.source,
+        changed: pending.changed,
+        restarting,
+    });
+
+    if !restarting {
+        // The UI already knows about its own saves
+        if pending.source == ChangeSource::External {
+            send_notification(app, "ChatCode Bot", "Configuration changed, restart the bot to apply it");
+        }
+        return;
+    }
+
+    // A restart waits out the stop grace; the other profiles keep being polled meanwhile
+    let app = app.clone();
+    let profile_id = profile_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let controller: State<BotController> = app.state();
+        match controller.restart(&profile_id, StopReason::ConfigChange).await {
+            Ok(launch) => {
+                let _ = app.emit("bot-status", launch.message());
+            }
+            Err(e) => {
+                error!("Restart after configuration change failed: {}", e);
+                let _ = app.emit("bot-error", e);
+            }
+        }
+    });
+}
+
+pub fn spawn(app: AppHandle) {
+    thread::spawn(move || {
+        let mut watches: HashMap<String, Watch> = HashMap::new();
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let config = app.state::<BotState>().supervisor_config();
+            let watcher: State<EnvWatcher> = app.state();
+
+            for profile in profiles::load_all() {
+                let bot = app.state::<BotState>().instance(&profile.id);
+                if config.env_reload == EnvReload::Off || lifecycle::current(&bot) != BotPhase::Ready {
+                    watches.remove(&profile.id);
+                    continue;
+                }
+
+                let watch_src = config.watch_sources
+                    && matches!(launch::load(&profile.project_path), LaunchMode::Dev);
+                let current = fingerprint(&profile.project_path, watch_src);
+                let run_id = bot.run_id.load(Ordering::SeqCst);
+
+                // Changes made before this run started are already in effect
+                let watch = watches.entry(profile.id.clone()).or_insert_with(|| Watch {
+                    run_id,
+                    fingerprint: current.clone(),
+                    pending: None,
+                });
+                if watch.run_id != run_id {
+                    *watch = Watch { run_id, fingerprint: current, pending: None };
+                    continue;
+                }
+
+                if current != watch.fingerprint {
+                    let env_path = Path::new(&profile.project_path).join(".env");
+                    let mut changed = Vec::new();
+                    let mut source = ChangeSource::App;
+                    if current.env_modified != watch.fingerprint.env_modified
+                        || current.env_len != watch.fingerprint.env_len
+                    {
+                        changed.push(".env".to_string());
+                        if !watcher.is_own_write(&env_path, current.env_modified) {
+                            source = ChangeSource::External;
+                        }
+                    }
+                    if current.src_modified != watch.fingerprint.src_modified {
+                        changed.push("src/".to_string());
+                        source = ChangeSource::External;
+                    }
+
+                    // Keep collecting until the files stay quiet for the debounce period
+                    let pending = watch.pending.get_or_insert(Pending {
+                        last_change: Instant::now(),
+                        source,
+                        changed: Vec::new(),
+                    });
+                    pending.last_change = Instant::now();
+                    if source == ChangeSource::External {
+                        pending.source = ChangeSource::External;
+                    }
+                    for name in changed {
+                        if !pending.changed.contains(&name) {
+                            pending.changed.push(name);
+                        }
+                    }
+                    watch.fingerprint = current;
+                }
+
+                let settled = watch.pending.as_ref().is_some_and(|pending| {
+                    pending.last_change.elapsed() >= Duration::from_millis(config.reload_debounce_ms)
+                });
+                if settled {
+                    if let Some(pending) = watch.pending.take() {
+                        react(&app, &profile.id, pending, config.env_reload);
+                    }
+                }
+            }
+        }
+    });
+}
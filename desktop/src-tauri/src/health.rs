@@ -0,0 +1,125 @@
+// Readiness probing against the bot's Express `/health` endpoint.
+//
+// A spawned bot stays in `Starting` until `/health` answers. The outcome is
+// broadcast as `bot-readiness` and handed back to whoever started the bot.
+
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use log::{info, warn};
+
+use crate::controller::BotController;
+use crate::history::StopReason;
+use crate::lifecycle::{self, BotPhase};
+use crate::{get_http_client, BotInstance, BotState};
+
+// Delay between readiness probes
+const PROBE_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Readiness {
+    Ready { pid: u32, elapsed_ms: u64 },
+    Failed { reason: String, output: Vec<String> },
+}
+
+impl Readiness {
+    pub fn message(&self) -> String {
+        match self {
+            Readiness::Ready { pid, elapsed_ms } => format!(
+                "Bot is ready (PID: {}, {:.1}s)",
+                pid,
+                *elapsed_ms as f64 / 1000.0
+            ),
+            Readiness::Failed { reason, output } if output.is_empty() => reason.clone(),
+            Readiness::Failed { reason, output } => {
+                format!("{}\n\n{}", reason, output.join("\n"))
+            }
+        }
+    }
+}
+
+// Whether `/health` on `base_url` currently answers with a success status
+pub async fn probe(client: &reqwest::Client, base_url: &str) -> bool {
+    match client.get(format!("{}/health", base_url)).send().await {
+        Ok(response) => response.status().is_success(),
+        Err(_) => false,
+    }
+}
+
+fn startup_output(bot: &BotInstance) -> Vec<String> {
+    bot
+        .startup_output
+        .lock()
+        .map(|output| output.clone())
+        .unwrap_or_default()
+}
+
+fn failed(bot: &BotInstance, reason: &str) -> Readiness {
+    Readiness::Failed {
+        reason: reason.to_string(),
+        output: startup_output(bot),
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct ReadinessEvent {
+    profile_id: String,
+    #[serde(flatten)]
+    readiness: Readiness,
+}
+
+// Poll `/health` for the run `run_id` until it answers, the process dies or
+// the startup timeout expires. A bot that never comes up is stopped again.
+pub async fn await_ready(
+    app: AppHandle,
+    bot: Arc<BotInstance>,
+    run_id: u64,
+    pid: u32,
+    base_url: String,
+) -> Readiness {
+    let timeout = Duration::from_millis(app.state::<BotState>().supervisor_config().startup_timeout_ms);
+    let client = get_http_client();
+    let started = Instant::now();
+
+    let readiness = loop {
+        if bot.run_id.load(Ordering::SeqCst) != run_id {
+            break failed(&bot, "Bot was stopped before it became ready");
+        }
+        if lifecycle::current(&bot) != BotPhase::Starting {
+            break failed(&bot, "Bot exited during startup");
+        }
+        if probe(&client, &base_url).await {
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            if lifecycle::transition(&app, &bot, BotPhase::Ready).is_err() {
+                break failed(&bot, "Bot exited during startup");
+            }
+            info!("Bot [{}] ready after {}ms", bot.profile_id, elapsed_ms);
+            break Readiness::Ready { pid, elapsed_ms };
+        }
+        if started.elapsed() >= timeout {
+            warn!(
+                "Bot [{}] did not answer {}/health within {}ms, stopping it",
+                bot.profile_id,
+                base_url,
+                timeout.as_millis()
+            );
+            let output = startup_output(&bot);
+            let controller: State<BotController> = app.state();
+            let _ = controller.stop(&bot.profile_id, StopReason::StartupTimeout).await;
+            break Readiness::Failed {
+                reason: format!("Bot did not become healthy within {}s", timeout.as_secs()),
+                output,
+            };
+        }
+        tokio::time::sleep(PROBE_INTERVAL).await;
+    };
+
+    let _ = app.emit("bot-readiness", ReadinessEvent {
+        profile_id: bot.profile_id.clone(),
+        readiness: readiness.clone(),
+    });
+    readiness
+}
@@ -0,0 +1,321 @@
+// Persistent ledger of bot runs.
+//
+// Every run is appended to ~/.chatcode/runs.jsonl when it ends, with how long
+// it ran, why it stopped, how it exited and the peak memory of its process
+// tree. The run in progress is kept on the bot instance until then.
+
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use log::error;
+
+use crate::{chatcode_dir, profiles, BotInstance, BotState};
+
+// Why a run ended
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    // Stop or restart from the dashboard
+    User,
+    Tray,
+    // Native menu bar items
+    Menu,
+    Shortcut,
+    // Exited on its own
+    Crash,
+    Watchdog,
+    Schedule,
+    // Restarted after its .env changed
+    ConfigChange,
+    // Never answered /health during startup
+    StartupTimeout,
+    // The desktop app quit
+    AppExit,
+    // An adopted bot stopped by whoever started it
+    External,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    // Profile id and start time in milliseconds, unique per profile
+    pub id: String,
+    pub profile_id: String,
+    pub started_at: String,
+    // None while the run is in progress
+    pub stopped_at: Option<String>,
+    pub stop_reason: Option<StopReason>,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    pub peak_memory_mb: Option<f64>,
+    // Started outside this session and adopted or reattached
+    pub adopted: bool,
+}
+
+impl RunRecord {
+    pub fn started(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.started_at)
+            .ok()
+            .map(|at| at.with_timezone(&Local))
+    }
+
+    fn stopped(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(self.stopped_at.as_deref()?)
+            .ok()
+            .map(|at| at.with_timezone(&Local))
+    }
+
+    // The part of the run inside [from, to)
+    fn overlap(&self, from: DateTime<Local>, to: DateTime<Local>) -> ChronoDuration {
+        let (Some(start), end) = (self.started(), self.stopped().unwrap_or(to)) else {
+            return ChronoDuration::zero();
+        };
+        let (start, end) = (start.max(from), end.min(to));
+        if end > start {
+            end - start
+        } else {
+            ChronoDuration::zero()
+        }
+    }
+}
+
+fn path() -> PathBuf {
+    chatcode_dir().join("runs.jsonl")
+}
+
+fn load_all() -> Vec<RunRecord> {
+    let Ok(content) = std::fs::read_to_string(path()) else {
+        return Vec::new();
+    };
+    // Skip lines cut short by a crash mid-write
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+fn append(record: &RunRecord) -> Result<(), String> {
+    std::fs::create_dir_all(chatcode_dir())
+        .map_err(|e| format!("Failed to create .chatcode directory: {}", e))?;
+    let line = serde_json::to_string(record).map_err(|e| format!("Failed to serialize run: {}", e))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path())
+        .map_err(|e| format!("Failed to open run history: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write run history: {}", e))
+}
+
+// Open a record for a run that started at `started_at`
+pub fn begin(bot: &BotInstance, started_at: DateTime<Local>, adopted: bool) -> String {
+    let id = format!("{}-{}", bot.profile_id, started_at.timestamp_millis());
+    let record = RunRecord {
+        id: id.clone(),
+        profile_id: bot.profile_id.clone(),
+        started_at: started_at.to_rfc3339(),
+        stopped_at: None,
+        stop_reason: None,
+        exit_code: None,
+        signal: None,
+        peak_memory_mb: None,
+        adopted,
+    };
+    if let Ok(mut run) = bot.current_run.lock() {
+        *run = Some(record);
+    }
+    id
+}
+
+// Fold a memory reading of the run's process tree into its peak
+pub fn sample_memory(bot: &BotInstance, memory_mb: f64) {
+    if let Ok(mut run) = bot.current_run.lock() {
+        if let Some(run) = run.as_mut() {
+            run.peak_memory_mb = Some(run.peak_memory_mb.map_or(memory_mb, |peak| peak.max(memory_mb)));
+        }
+    }
+}
+
+// Close the run in progress and append it to the ledger
+pub fn finish(bot: &BotInstance, reason: StopReason, status: Option<ExitStatus>) {
+    let Some(mut record) = bot.current_run.lock().ok().and_then(|mut run| run.take()) else {
+        return;
+    };
+    record.stopped_at = Some(Local::now().to_rfc3339());
+    record.stop_reason = Some(reason);
+    record.exit_code = status.and_then(|status| status.code());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        record.signal = status.and_then(|status| status.signal());
+    }
+    if let Err(e) = append(&record) {
+        error!("{}", e);
+    }
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Local>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|at| at.with_timezone(&Local))
+        .map_err(|e| format!("Invalid time {}: {}", value, e))
+}
+
+// Completed runs and runs in progress for a profile
+pub fn runs_of(state: &BotState, profile_id: &str) -> Vec<RunRecord> {
+    let mut runs: Vec<RunRecord> = load_all()
+        .into_iter()
+        .filter(|run| run.profile_id == profile_id)
+        .collect();
+    if let Some(current) = state
+        .instance(profile_id)
+        .current_run
+        .lock()
+        .ok()
+        .and_then(|run| run.clone())
+    {
+        runs.push(current);
+    }
+    runs
+}
+
+// Runs overlapping [from, to), newest first; both ends are RFC 3339 and optional
+#[tauri::command]
+pub fn list_runs(
+    state: tauri::State<BotState>,
+    profile_id: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<RunRecord>, String> {
+    let from = from.as_deref().map(parse_time).transpose()?;
+    let to = to.as_deref().map(parse_time).transpose()?;
+
+    let mut runs: Vec<RunRecord> = runs_of(&state, &profiles::resolve_id(profile_id))
+        .into_iter()
+        .filter(|run| {
+            let starts_before_end = match (run.started(), to) {
+                (Some(start), Some(to)) => start < to,
+                _ => true,
+            };
+            let ends_after_start = match (run.stopped(), from) {
+                (Some(stop), Some(from)) => stop >= from,
+                _ => true,
+            };
+            starts_before_end && ends_after_start
+        })
+        .collect();
+    runs.sort_by(|a, b| b.started().cmp(&a.started()));
+    Ok(runs)
+}
+
+#[derive(Clone, Serialize)]
+pub struct Availability {
+    profile_id: String,
+    // Percent of each period the bot was running
+    last_24h: f64,
+    last_7d: f64,
+    last_30d: f64,
+}
+
+// Percent of the `period` up to `now` covered by `runs`
+fn uptime_percent(runs: &[RunRecord], now: DateTime<Local>, period: ChronoDuration) -> f64 {
+    let from = now - period;
+    let up: i64 = runs.iter().map(|run| run.overlap(from, now).num_seconds()).sum();
+    (up as f64 / period.num_seconds() as f64 * 100.0).min(100.0)
+}
+
+#[tauri::command]
+pub fn get_availability(state: tauri::State<BotState>, profile_id: Option<String>) -> Availability {
+    let profile_id = profiles::resolve_id(profile_id);
+    let runs = runs_of(&state, &profile_id);
+    let now = Local::now();
+    let percent = |period: ChronoDuration| uptime_percent(&runs, now, period);
+
+    Availability {
+        last_24h: percent(ChronoDuration::hours(24)),
+        last_7d: percent(ChronoDuration::days(7)),
+        last_30d: percent(ChronoDuration::days(30)),
+        profile_id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        parse_time("2026-05-04T12:00:00+00:00").expect("valid time")
+    }
+
+    // A run from `started` to `stopped` hours relative to `now()`; None is still running
+    fn run(started: i64, stopped: Option<i64>) -> RunRecord {
+        let at = |hours: i64| (now() + ChronoDuration::hours(hours)).to_rfc3339();
+        RunRecord {
+            id: format!("{}-{}", profiles::DEFAULT_PROFILE, started),
+            profile_id: profiles::DEFAULT_PROFILE.to_string(),
+            started_at: at(started),
+            stopped_at: stopped.map(at),
+            stop_reason: stopped.map(|_| StopReason::User),
+            exit_code: None,
+            signal: None,
+            peak_memory_mb: None,
+            adopted: false,
+        }
+    }
+
+    fn hours_inside(run: &RunRecord, from: i64, to: i64) -> i64 {
+        let at = |hours: i64| now() + ChronoDuration::hours(hours);
+        run.overlap(at(from), at(to)).num_hours()
+    }
+
+    #[test]
+    fn overlap_of_a_run_inside_the_window_is_the_whole_run() {
+        assert_eq!(hours_inside(&run(-5, Some(-2)), -10, 0), 3);
+    }
+
+    #[test]
+    fn overlap_is_cut_at_the_start_of_the_window() {
+        assert_eq!(hours_inside(&run(-30, Some(-20)), -24, 0), 4);
+    }
+
+    #[test]
+    fn overlap_is_cut_at_the_end_of_the_window() {
+        assert_eq!(hours_inside(&run(-5, Some(-1)), -10, -3), 2);
+    }
+
+    #[test]
+    fn run_in_progress_counts_up_to_the_end_of_the_window() {
+        assert_eq!(hours_inside(&run(-6, None), -24, 0), 6);
+        // Started before the window and still running: all of it
+        assert_eq!(hours_inside(&run(-48, None), -24, 0), 24);
+    }
+
+    #[test]
+    fn run_outside_the_window_does_not_overlap() {
+        assert_eq!(hours_inside(&run(-40, Some(-30)), -24, 0), 0);
+        // Stopped exactly where the window starts
+        assert_eq!(hours_inside(&run(-30, Some(-24)), -24, 0), 0);
+        assert_eq!(hours_inside(&run(-6, None), -24, -12), 0);
+    }
+
+    #[test]
+    fn run_without_a_valid_start_does_not_overlap() {
+        let mut broken = run(-5, Some(-2));
+        broken.started_at = "yesterday".to_string();
+        assert_eq!(hours_inside(&broken, -10, 0), 0);
+    }
+
+    #[test]
+    fn uptime_adds_up_runs_in_the_period() {
+        // 4h of a run overlapping the start, 2h stopped, 6h still running
+        let runs = [run(-30, Some(-20)), run(-10, Some(-8)), run(-6, None)];
+        assert_eq!(uptime_percent(&runs, now(), ChronoDuration::hours(24)), 50.0);
+        assert_eq!(uptime_percent(&[], now(), ChronoDuration::hours(24)), 0.0);
+    }
+
+    #[test]
+    fn uptime_is_capped_at_one_hundred_percent() {
+        // An adopted run and the one it overlaps with
+        let runs = [run(-30, None), run(-12, None)];
+        assert_eq!(uptime_percent(&runs, now(), ChronoDuration::hours(24)), 100.0);
+    }
+}
@@ -0,0 +1,99 @@
+// How the bot is launched for a project.
+//
+// Dev runs the TypeScript sources directly with `tsx` through `pnpm run dev`
+// (no file watching, so source edits need a restart), production runs the
+// build produced by `install_dependencies` with NODE_ENV=production unless
+// .env or the profile sets it, and custom runs any command line.
+// The choice is persisted per project in ~/.chatcode/launch_modes.json.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::chatcode_dir;
+
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum LaunchMode {
+    #[default]
+    Dev,
+    Production,
+    Custom {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+        // Defaults to the project directory
+        #[serde(default)]
+        working_dir: Option<String>,
+    },
+}
+
+impl LaunchMode {
+    // Build the command for this mode; stdio and process group are set by the caller
+    pub fn command(&self, project_path: &str) -> Result<Command, String> {
+        match self {
+            LaunchMode::Dev => {
+                // Run pnpm directly (PATH is fixed by fix_path_env at startup)
+                let mut command = Command::new("pnpm");
+                command.args(["run", "dev"]).current_dir(project_path);
+                Ok(command)
+            }
+            LaunchMode::Production => {
+                let entry = Path::new(project_path).join("dist/main.js");
+                if !entry.exists() {
+                    return Err(format!(
+                        "Production build not found at {}, install dependencies first",
+                        entry.display()
+                    ));
+                }
+                let mut command = Command::new("node");
+                command.arg("dist/main.js").current_dir(project_path);
+                Ok(command)
+            }
+            LaunchMode::Custom { program, args, working_dir } => {
+                if program.trim().is_empty() {
+                    return Err("Custom launch command is empty".to_string());
+                }
+                let mut command = Command::new(program);
+                command
+                    .args(args)
+                    .current_dir(working_dir.as_deref().unwrap_or(project_path));
+                Ok(command)
+            }
+        }
+    }
+}
+
+fn store_path() -> PathBuf {
+    chatcode_dir().join("launch_modes.json")
+}
+
+fn load_all() -> HashMap<String, LaunchMode> {
+    std::fs::read_to_string(store_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn load(project_path: &str) -> LaunchMode {
+    load_all().remove(project_path).unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn get_launch_mode(project_path: String) -> LaunchMode {
+    load(&project_path)
+}
+
+#[tauri::command]
+pub fn set_launch_mode(project_path: String, mode: LaunchMode) -> Result<(), String> {
+    let mut modes = load_all();
+    modes.insert(project_path, mode);
+
+    std::fs::create_dir_all(chatcode_dir())
+        .map_err(|e| format!("Failed to create .chatcode directory: {}", e))?;
+    let content = serde_json::to_string_pretty(&modes)
+        .map_err(|e| format!("Failed to serialize launch modes: {}", e))?;
+    std::fs::write(store_path(), content)
+        .map_err(|e| format!("Failed to write launch modes: {}", e))
+}
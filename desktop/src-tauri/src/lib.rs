@@ -1,11 +1,13 @@
 use serde::Serialize;
-use std::io::{BufRead, BufReader};
-use std::process::{Child, Command, Stdio};
-use std::sync::Mutex;
-use std::thread;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
 use std::time::Duration;
 use tauri::{
-    menu::{Menu, MenuBuilder, MenuItem, PredefinedMenuItem, SubmenuBuilder},
+    menu::{Menu, MenuBuilder, MenuItem, SubmenuBuilder},
     tray::TrayIconBuilder,
     AppHandle, Emitter, Manager, RunEvent, State, WindowEvent,
 };
@@ -14,6 +16,48 @@ use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut}
 use tauri_plugin_notification::NotificationExt;
 use log::{info, error};
 
+mod adopt;
+mod botlog;
+mod controller;
+mod environment;
+mod envwatch;
+mod health;
+mod history;
+mod launch;
+mod lifecycle;
+mod limits;
+mod livelog;
+mod logbuffer;
+mod logs;
+mod output;
+mod pidfile;
+mod process;
+mod procfs;
+mod profiles;
+mod redact;
+mod resources;
+mod schedule;
+mod secrets;
+mod supervisor;
+mod watchdog;
+
+use botlog::{BotLog, RunLog};
+use controller::{BotAction, BotController};
+use envwatch::EnvWatcher;
+use health::Readiness;
+use history::{RunRecord, StopReason};
+use lifecycle::BotPhase;
+use limits::{AppliedLimits, LimitKind};
+use livelog::LiveLog;
+use logbuffer::LogBuffer;
+use logs::{LogEntry, LogParser, LogStream};
+use process::{BotProcess, StopMethod};
+use procfs::CpuSampler;
+use profiles::BotProfile;
+use redact::Redactor;
+use resources::{ProcessUsage, TreeUsage};
+use supervisor::SupervisorConfig;
+
 // Get or create a shared HTTP client for metrics/analytics requests
 fn get_http_client() -> reqwest::Client {
     reqwest::Client::builder()
@@ -23,6 +67,12 @@ fn get_http_client() -> reqwest::Client {
         .expect("Failed to create HTTP client")
 }
 
+// Desktop-owned state directory (~/.chatcode)
+pub(crate) fn chatcode_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(home).join(".chatcode")
+}
+
 // Helper function to send system notification
 fn send_notification(app: &AppHandle, title: &str, body: &str) {
     let _ = app.notification()
@@ -40,292 +90,600 @@ fn update_tray_status(_app: &AppHandle, _is_running: bool, _uptime_secs: Option<
     // Tauri 2.0 requires storing TrayIcon in managed state to access later
 }
 
-// Bot process state
-pub struct BotState {
-    process: Mutex<Option<Child>>,
+// Tray menu: start/stop/restart per profile, in a submenu each when there are several
+fn build_tray_menu<R: tauri::Runtime, M: Manager<R>>(manager: &M) -> tauri::Result<Menu<R>> {
+    let profiles = profiles::load_all();
+    let mut builder = MenuBuilder::new(manager)
+        .item(&MenuItem::with_id(manager, "dashboard", "Dashboard", true, None::<&str>)?)
+        .separator();
+
+    if let [profile] = profiles.as_slice() {
+        builder = builder
+            .item(&MenuItem::with_id(manager, format!("start:{}", profile.id), "启动", true, None::<&str>)?)
+            .item(&MenuItem::with_id(manager, format!("stop:{}", profile.id), "停止", true, None::<&str>)?)
+            .item(&MenuItem::with_id(manager, format!("restart:{}", profile.id), "重启", true, None::<&str>)?);
+    } else {
+        for profile in &profiles {
+            let submenu = SubmenuBuilder::new(manager, &profile.name)
+                .item(&MenuItem::with_id(manager, format!("start:{}", profile.id), "启动", true, None::<&str>)?)
+                .item(&MenuItem::with_id(manager, format!("stop:{}", profile.id), "停止", true, None::<&str>)?)
+                .item(&MenuItem::with_id(manager, format!("restart:{}", profile.id), "重启", true, None::<&str>)?)
+                .build()?;
+            builder = builder.item(&submenu);
+        }
+    }
+
+    builder
+        .separator()
+        .item(&MenuItem::with_id(manager, "quit", "退出", true, None::<&str>)?)
+        .build()
+}
+
+// Called after profiles change so the tray lists the current set
+pub(crate) fn rebuild_tray_menu(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+    match build_tray_menu(app) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => error!("Failed to rebuild tray menu: {}", e),
+    }
+}
+
+// Process state of one bot profile
+pub struct BotInstance {
+    profile_id: String,
+    process: Mutex<Option<BotProcess>>,
     start_time: Mutex<Option<std::time::Instant>>,
+    // Bumped on every start and stop so stale watchers and restarts bail out
+    run_id: AtomicU64,
+    phase: Mutex<BotPhase>,
+    last_exit: Mutex<Option<BotExit>>,
+    // Last stderr lines of the current run, attached to the exit report
+    stderr_tail: Arc<Mutex<VecDeque<String>>>,
+    // First output lines of the current run, reported when startup fails
+    startup_output: Arc<Mutex<Vec<String>>>,
+    // Automatic restarts inside the supervisor window
+    restarts: Mutex<VecDeque<std::time::Instant>>,
+    // CPU time per process seen by the previous health poll
+    cpu_sampler: Mutex<CpuSampler>,
+    // Resource limits of the current run
+    limits: Mutex<AppliedLimits>,
+    // Ledger record of the run in progress
+    current_run: Mutex<Option<RunRecord>>,
 }
 
-impl Default for BotState {
-    fn default() -> Self {
+impl BotInstance {
+    fn new(profile_id: &str) -> Self {
         Self {
+            profile_id: profile_id.to_string(),
             process: Mutex::new(None),
             start_time: Mutex::new(None),
+            run_id: AtomicU64::new(0),
+            phase: Mutex::new(BotPhase::Stopped),
+            last_exit: Mutex::new(None),
+            stderr_tail: Arc::new(Mutex::new(VecDeque::new())),
+            startup_output: Arc::new(Mutex::new(Vec::new())),
+            restarts: Mutex::new(VecDeque::new()),
+            cpu_sampler: Mutex::new(CpuSampler::default()),
+            limits: Mutex::new(AppliedLimits::default()),
+            current_run: Mutex::new(None),
         }
     }
 }
 
+// Bot process state, one instance per profile
+pub struct BotState {
+    instances: Mutex<HashMap<String, Arc<BotInstance>>>,
+    supervisor: Mutex<SupervisorConfig>,
+}
+
+impl Default for BotState {
+    fn default() -> Self {
+        Self {
+            instances: Mutex::new(HashMap::new()),
+            supervisor: Mutex::new(SupervisorConfig::load()),
+        }
+    }
+}
+
+impl BotState {
+    // The instance for `profile_id`, created on first use
+    fn instance(&self, profile_id: &str) -> Arc<BotInstance> {
+        let mut instances = self.instances.lock().unwrap_or_else(|e| e.into_inner());
+        instances
+            .entry(profile_id.to_string())
+            .or_insert_with(|| Arc::new(BotInstance::new(profile_id)))
+            .clone()
+    }
+
+    fn instances(&self) -> Vec<Arc<BotInstance>> {
+        let instances = self.instances.lock().unwrap_or_else(|e| e.into_inner());
+        instances.values().cloned().collect()
+    }
+
+    fn remove_instance(&self, profile_id: &str) {
+        if let Ok(mut instances) = self.instances.lock() {
+            instances.remove(profile_id);
+        }
+    }
+
+    fn supervisor_config(&self) -> SupervisorConfig {
+        self.supervisor
+            .lock()
+            .map(|config| config.clone())
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Clone, Serialize)]
 pub struct BotStatus {
+    profile_id: String,
     is_running: bool,
+    phase: BotPhase,
     uptime_seconds: u64,
     pid: Option<u32>,
+    last_exit: Option<BotExit>,
+}
+
+// Number of stderr lines kept for the exit report
+const STDERR_TAIL_LINES: usize = 20;
+
+// Number of output lines kept for the startup failure report
+const STARTUP_OUTPUT_LINES: usize = 100;
+
+fn push_startup_output(output: &Mutex<Vec<String>>, line: &str) {
+    if let Ok(mut output) = output.lock() {
+        if output.len() < STARTUP_OUTPUT_LINES {
+            output.push(line.to_string());
+        }
+    }
 }
 
+// Send a line of bot output to the log files, the in-memory buffer and the live view
+fn record_output(app: &AppHandle, run_log: Option<&RunLog>, entry: LogEntry) {
+    let config = app.state::<BotState>().supervisor_config();
+    app.state::<BotLog>().write(&entry, &config);
+    if let Some(run_log) = run_log {
+        run_log.write(&entry);
+    }
+    app.state::<LogBuffer>().push(entry.clone(), config.log_buffer_lines);
+    app.state::<LiveLog>().push(entry);
+}
+
+// Tail the bot's output files into the logs until its process group is gone.
+// Returns the stderr reader, which also keeps the tail for the exit report.
+pub(crate) fn capture_output(
+    app: &AppHandle,
+    bot: &Arc<BotInstance>,
+    pgid: u32,
+    stdout: std::fs::File,
+    stderr: std::fs::File,
+    run_log: Option<Arc<RunLog>>,
+    redactor: Arc<Redactor>,
+) -> JoinHandle<()> {
+    // Captured into the log files and the batched live view
+    {
+        let bot = bot.clone();
+        let app = app.clone();
+        let run_log = run_log.clone();
+        let redactor = redactor.clone();
+        thread::spawn(move || {
+            let mut parser = LogParser::new(&bot.profile_id, LogStream::Stdout);
+            output::tail(stdout, &bot, pgid, |line| {
+                let line = redactor.redact(&line);
+                record_output(&app, run_log.as_deref(), parser.parse(&line));
+                push_startup_output(&bot.startup_output, &line);
+            });
+        });
+    }
+
+    // The same way, keeping the tail for the exit report
+    if let Ok(mut tail) = bot.stderr_tail.lock() {
+        tail.clear();
+    }
+    let bot = bot.clone();
+    let app = app.clone();
+    thread::spawn(move || {
+        let mut parser = LogParser::new(&bot.profile_id, LogStream::Stderr);
+        output::tail(stderr, &bot, pgid, |line| {
+            let line = redactor.redact(&line);
+            record_output(&app, run_log.as_deref(), parser.parse(&line));
+            push_startup_output(&bot.startup_output, &line);
+            if let Ok(mut tail) = bot.stderr_tail.lock() {
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
+            }
+        });
+    })
+}
+
+// How the last run ended when the bot exited on its own (`bot-exited` payload)
 #[derive(Clone, Serialize)]
-pub struct LogEntry {
-    level: String,
-    message: String,
-    timestamp: String,
+pub struct BotExit {
+    profile_id: String,
+    code: Option<i32>,
+    signal: Option<i32>,
+    success: bool,
+    uptime_seconds: u64,
+    exited_at: String,
+    stderr_tail: Vec<String>,
+    // Set when a configured resource limit killed the bot
+    limit_exceeded: Option<LimitKind>,
+}
+
+impl BotExit {
+    // `status` is unknown for an adopted bot, which is not our child
+    fn new(
+        profile_id: &str,
+        status: Option<ExitStatus>,
+        uptime_seconds: u64,
+        stderr_tail: Vec<String>,
+        limit_exceeded: Option<LimitKind>,
+    ) -> Self {
+        #[cfg(unix)]
+        let signal = status.and_then(|status| {
+            use std::os::unix::process::ExitStatusExt;
+            status.signal()
+        });
+        #[cfg(not(unix))]
+        let signal = None;
+
+        Self {
+            profile_id: profile_id.to_string(),
+            code: status.and_then(|status| status.code()),
+            signal,
+            success: status.is_some_and(|status| status.success()),
+            uptime_seconds,
+            exited_at: chrono::Local::now().to_rfc3339(),
+            stderr_tail,
+            limit_exceeded,
+        }
+    }
 }
 
 #[derive(Clone, Serialize)]
 pub struct BotHealth {
+    profile_id: String,
     is_running: bool,
     is_responsive: bool,
     uptime_seconds: u64,
     pid: Option<u32>,
+    // Resident set size
     memory_mb: Option<f64>,
+    virtual_memory_mb: Option<f64>,
+    // Percent of one core since the previous poll; None on the first poll
+    cpu_percent: Option<f64>,
+    threads: Option<u32>,
+    open_fds: Option<u32>,
+    is_zombie: bool,
+    // The managed process and everything below it
+    processes: Vec<ProcessUsage>,
+    total_memory_mb: f64,
+    total_cpu_percent: f64,
 }
 
-// Internal function to stop bot (used by restart)
-fn stop_bot_internal(state: &BotState) -> Result<String, String> {
-    let mut process_guard = state.process.lock().map_err(|e| e.to_string())?;
+// Result of stopping the bot, reported back to the caller
+#[derive(Clone, Serialize)]
+pub struct StopReport {
+    method: StopMethod,
+    elapsed_ms: u64,
+}
 
-    if let Some(mut child) = process_guard.take() {
-        // Try graceful shutdown first (SIGTERM equivalent)
-        #[cfg(unix)]
-        {
-            let _ = Command::new("kill")
-                .args(["-TERM", &child.id().to_string()])
-                .spawn();
+impl StopReport {
+    fn message(&self) -> String {
+        let secs = self.elapsed_ms as f64 / 1000.0;
+        match self.method {
+            StopMethod::Graceful => format!("Bot stopped gracefully in {:.1}s", secs),
+            StopMethod::Killed => format!("Bot did not exit in time, force killed after {:.1}s", secs),
+            StopMethod::Cancelled => "Pending automatic restart cancelled".to_string(),
         }
+    }
+}
 
-        // Wait a bit for graceful shutdown
-        thread::sleep(Duration::from_millis(500));
-
-        // Force kill if still running
-        let _ = child.kill();
-        let _ = child.wait();
+// Internal function to stop bot (used by restart)
+fn stop_bot_internal(app: &AppHandle, bot: &BotInstance, reason: StopReason) -> Result<StopReport, String> {
+    // Invalidate the watcher and any pending automatic restart
+    bot.run_id.fetch_add(1, Ordering::SeqCst);
+
+    // After a crash there is no process, only the crash state to clear
+    let phase = lifecycle::current(bot);
+    if matches!(phase, BotPhase::Crashed | BotPhase::Backoff)
+        && lifecycle::transition(app, bot, BotPhase::Stopped).is_ok()
+    {
+        if phase == BotPhase::Backoff {
+            return Ok(StopReport { method: StopMethod::Cancelled, elapsed_ms: 0 });
+        }
+        return Err("Bot is not running".to_string());
+    }
 
-        let mut start_time = state.start_time.lock().map_err(|e| e.to_string())?;
-        *start_time = None;
+    // Take the process out so the lock isn't held during the grace period
+    let running = bot.process.lock().map_err(|e| e.to_string())?.take();
+    let Some(mut running) = running else {
+        return Err("Bot is not running".to_string());
+    };
+    let pgid = running.pgid();
+    let _ = lifecycle::transition(app, bot, BotPhase::Stopping);
+
+    let grace = Duration::from_millis(app.state::<BotState>().supervisor_config().stop_grace_period_ms);
+    let started = std::time::Instant::now();
+    let method = process::terminate(&mut running, grace);
+    history::finish(bot, reason, running.exit_status());
+    let report = StopReport {
+        method,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+    info!("Bot [{}] stopped: {:?} after {}ms", bot.profile_id, report.method, report.elapsed_ms);
 
-        Ok("Bot stopped successfully".to_string())
-    } else {
-        Err("Bot is not running".to_string())
+    // Make sure nothing in the tree survived (node, claude subprocesses)
+    #[cfg(unix)]
+    if !process::wait_group_exit(pgid, Duration::from_secs(2)) {
+        error!("Bot process group {} still has live members after stop", pgid);
     }
+    #[cfg(not(unix))]
+    let _ = pgid;
+
+    pidfile::remove(&bot.profile_id);
+    let mut start_time = bot.start_time.lock().map_err(|e| e.to_string())?;
+    *start_time = None;
+    let _ = lifecycle::transition(app, bot, BotPhase::Stopped);
+
+    Ok(report)
 }
 
 // Commands
 
 #[tauri::command]
-async fn get_bot_status(state: State<'_, BotState>) -> Result<BotStatus, String> {
-    let (mut is_running, uptime_seconds, pid) = {
-        let process = state.process.lock().map_err(|e| e.to_string())?;
-        let start_time = state.start_time.lock().map_err(|e| e.to_string())?;
-
-        let is_running = process.is_some();
-        let uptime_seconds = if let Some(start) = *start_time {
-            start.elapsed().as_secs()
-        } else {
-            0
-        };
-        let pid = process.as_ref().map(|p| p.id());
+async fn get_bot_status(
+    app: AppHandle,
+    state: State<'_, BotState>,
+    profile_id: Option<String>,
+) -> Result<BotStatus, String> {
+    let profile = profiles::get(&profiles::resolve_id(profile_id))?;
+    let bot = state.instance(&profile.id);
+
+    // If no Tauri-managed process, check if an external bot is running on the profile's port
+    // and adopt it so it can be stopped and monitored like our own
+    let external = !lifecycle::current(&bot).is_running() && adopt::detect(&app, &bot, &profile).await;
+
+    let last_exit = bot.last_exit.lock().map_err(|e| e.to_string())?.clone();
+    let phase = lifecycle::current(&bot);
+    let process = bot.process.lock().map_err(|e| e.to_string())?;
+    let start_time = bot.start_time.lock().map_err(|e| e.to_string())?;
 
-        (is_running, uptime_seconds, pid)
+    let uptime_seconds = if let Some(start) = *start_time {
+        start.elapsed().as_secs()
+    } else {
+        0
     };
-    // Locks are dropped here before async operation
-
-    // If no Tauri-managed process, check if external bot is running on port 3002
-    if !is_running {
-        let client = get_http_client();
-        match client
-            .get("http://127.0.0.1:3002/metrics")
-            .send()
-            .await
-        {
-            Ok(response) => {
-                is_running = response.status().is_success();
-                info!("External bot check: is_running={}", is_running);
-            }
-            Err(e) => {
-                error!("External bot check failed: {}", e);
-            }
-        }
-    }
 
     Ok(BotStatus {
-        is_running,
+        profile_id: profile.id,
+        is_running: phase.is_running() || external,
+        phase,
         uptime_seconds,
-        pid,
+        pid: process.as_ref().map(|p| p.id()),
+        last_exit,
     })
 }
 
+// A freshly spawned bot; `readiness` resolves once `/health` answers or startup fails
+pub struct Launch {
+    pid: u32,
+    readiness: tauri::async_runtime::JoinHandle<Readiness>,
+}
+
+impl Launch {
+    fn message(&self) -> String {
+        format!("Bot started with PID: {}", self.pid)
+    }
+}
+
 // Internal function for starting bot (used by both command and tray menu)
 fn start_bot_internal(
-    _app: AppHandle,
-    state: &BotState,
-    project_path: String,
-) -> Result<String, String> {
-    let mut process_guard = state.process.lock().map_err(|e| e.to_string())?;
+    app: AppHandle,
+    bot: Arc<BotInstance>,
+    profile: &BotProfile,
+) -> Result<Launch, String> {
+    let mut process_guard = bot.process.lock().map_err(|e| e.to_string())?;
 
     if process_guard.is_some() {
         return Err("Bot is already running".to_string());
     }
-
-    // Run pnpm directly (PATH is fixed by fix_path_env at startup)
-    let mut child = Command::new("pnpm")
-        .args(["run", "dev"])
-        .current_dir(&project_path)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to start bot: {}", e))?;
+    let mut command = launch::load(&profile.project_path).command(&profile.project_path)?;
+    // Overrides and secrets are injected so they can stay off disk; .env is left to dotenv
+    let environment = environment::resolve(profile)?;
+    command.envs(environment.injected());
+    // Output is masked as it is read, before it reaches any log or event
+    let redactor = Arc::new(Redactor::new(&environment));
+    let mut applied_limits = limits::apply(&mut command, &profile.id, &profile.limits);
+    lifecycle::transition(&app, &bot, BotPhase::Starting)
+        .map_err(|_| "Bot is already running".to_string())?;
+
+    // Output goes to files rather than pipes so the bot outlives the app
+    let files = output::create(&bot.profile_id, LogStream::Stdout)
+        .and_then(|stdout| output::create(&bot.profile_id, LogStream::Stderr).map(|stderr| (stdout, stderr)));
+    let ((stdout, stdout_reader), (stderr, stderr_reader)) = match files {
+        Ok(files) => files,
+        Err(e) => {
+            let _ = lifecycle::transition(&app, &bot, BotPhase::Stopped);
+            return Err(e);
+        }
+    };
+    let spawned = process::isolate(&mut command)
+        .stdout(Stdio::from(stdout))
+        .stderr(Stdio::from(stderr))
+        .spawn();
+    let child = match spawned {
+        Ok(child) => child,
+        Err(e) => {
+            let _ = lifecycle::transition(&app, &bot, BotPhase::Stopped);
+            return Err(format!("Failed to start bot: {}", e));
+        }
+    };
 
     let pid = child.id();
-
-    // Capture stdout and write to log file (no high-frequency emit)
-    if let Some(stdout) = child.stdout.take() {
-        thread::spawn(move || {
-            let reader = BufReader::new(stdout);
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    // Write to log file via log plugin (not emit)
-                    info!(target: "bot", "{}", line);
-                }
-            }
-        });
+    applied_limits.confirm_cgroup(&profile.id, pid);
+    if let Err(e) = pidfile::write(&bot.profile_id, pid, &command) {
+        error!("{}", e);
     }
-
-    // Capture stderr and write to log file (no high-frequency emit)
-    if let Some(stderr) = child.stderr.take() {
-        thread::spawn(move || {
-            let reader = BufReader::new(stderr);
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    // Write to log file via log plugin (not emit)
-                    error!(target: "bot", "{}", line);
-                }
-            }
-        });
+    if let Ok(mut output) = bot.startup_output.lock() {
+        output.clear();
     }
+    // Opened before the readers start so the run log gets the first lines
+    let started_at = chrono::Local::now();
+    let run = history::begin(&bot, started_at, false);
+    let run_log = app.state::<BotLog>().open_run(
+        &run,
+        started_at,
+        app.state::<BotState>().supervisor_config().run_log_retention,
+    );
+    let stderr_reader = capture_output(&app, &bot, pid, stdout_reader, stderr_reader, run_log, redactor);
+
+    *process_guard = Some(BotProcess::Spawned(child));
+    if let Ok(mut limits) = bot.limits.lock() {
+        *limits = applied_limits;
+    }
+    let run_id = bot.run_id.fetch_add(1, Ordering::SeqCst) + 1;
 
-    *process_guard = Some(child);
-
-    let mut start_time = state.start_time.lock().map_err(|e| e.to_string())?;
+    let mut start_time = bot.start_time.lock().map_err(|e| e.to_string())?;
     *start_time = Some(std::time::Instant::now());
 
-    Ok(format!("Bot started with PID: {}", pid))
+    // Stays in Starting until /health answers
+    let readiness = tauri::async_runtime::spawn(health::await_ready(
+        app.clone(),
+        bot.clone(),
+        run_id,
+        pid,
+        profile.base_url(),
+    ));
+    drop(start_time);
+    drop(process_guard);
+    watchdog::spawn(app.clone(), bot.clone(), run_id);
+    supervisor::spawn_watcher(app, bot, run_id, Some(stderr_reader));
+
+    Ok(Launch { pid, readiness })
+}
+
+// Wait for a launch to pass its readiness check
+async fn await_launch(launch: Launch) -> Result<String, String> {
+    match launch.readiness.await {
+        Ok(readiness @ Readiness::Ready { .. }) => Ok(readiness.message()),
+        Ok(readiness) => Err(readiness.message()),
+        Err(e) => Err(format!("Readiness check failed: {}", e)),
+    }
 }
 
 #[tauri::command]
-fn start_bot(
-    app: AppHandle,
-    state: State<BotState>,
-    project_path: String,
+async fn start_bot(
+    controller: State<'_, BotController>,
+    profile_id: Option<String>,
 ) -> Result<String, String> {
-    start_bot_internal(app, &state, project_path)
+    let launch = controller.start(&profiles::resolve_id(profile_id)).await?;
+    await_launch(launch).await
 }
 
 #[tauri::command]
-fn stop_bot(state: State<BotState>) -> Result<String, String> {
-    stop_bot_internal(&state)
+async fn stop_bot(
+    controller: State<'_, BotController>,
+    profile_id: Option<String>,
+) -> Result<String, String> {
+    controller
+        .stop(&profiles::resolve_id(profile_id), StopReason::User)
+        .await
+        .map(|report| report.message())
 }
 
 #[tauri::command]
-fn restart_bot(
-    app: AppHandle,
-    state: State<BotState>,
-    project_path: String,
+async fn restart_bot(
+    controller: State<'_, BotController>,
+    profile_id: Option<String>,
 ) -> Result<String, String> {
-    // Stop if running
-    let _ = stop_bot_internal(&state);
-
-    // Wait a moment
-    thread::sleep(Duration::from_millis(300));
-
-    // Start again
-    start_bot_internal(app, &state, project_path)
+    let launch = controller.restart(&profiles::resolve_id(profile_id), StopReason::User).await?;
+    await_launch(launch).await
 }
 
 #[tauri::command]
-fn get_bot_health(state: State<BotState>) -> Result<BotHealth, String> {
-    let process = state.process.lock().map_err(|e| e.to_string())?;
-    let start_time = state.start_time.lock().map_err(|e| e.to_string())?;
-
-    let is_running = process.is_some();
-    let pid = process.as_ref().map(|p| p.id());
-
-    let uptime_seconds = if let Some(start) = *start_time {
-        start.elapsed().as_secs()
-    } else {
-        0
+fn get_bot_health(state: State<BotState>, profile_id: Option<String>) -> Result<BotHealth, String> {
+    let bot = state.instance(&profiles::resolve_id(profile_id));
+    // Copied out so the /proc scan below doesn't block the watcher or a stop
+    let pid = bot.process.lock().map_err(|e| e.to_string())?.as_ref().map(|p| p.id());
+    let is_running = pid.is_some();
+    let uptime_seconds = bot
+        .start_time
+        .lock()
+        .map_err(|e| e.to_string())?
+        .map_or(0, |start| start.elapsed().as_secs());
+
+    let stats = pid.and_then(procfs::stats);
+    // node and the claude sessions run below the wrapper we hold
+    let tree = match pid {
+        Some(pid) => bot
+            .cpu_sampler
+            .lock()
+            .map(|mut sampler| resources::measure(pid, &mut sampler))
+            .unwrap_or_default(),
+        None => TreeUsage::default(),
     };
+    if pid.is_some() {
+        history::sample_memory(&bot, tree.total_memory_mb);
+    }
 
-    // Get memory usage on macOS/Linux
-    let memory_mb = pid.and_then(|p| {
-        #[cfg(unix)]
-        {
-            let output = Command::new("ps")
-                .args(["-o", "rss=", "-p", &p.to_string()])
-                .output()
-                .ok()?;
-            let rss_kb: f64 = String::from_utf8_lossy(&output.stdout)
-                .trim()
-                .parse()
-                .ok()?;
-            Some(rss_kb / 1024.0)
-        }
-        #[cfg(not(unix))]
-        {
-            None
-        }
-    });
-
-    // Check if process is responsive (exists and not zombie)
-    let is_responsive = pid.map_or(false, |p| {
-        #[cfg(unix)]
-        {
-            Command::new("kill")
-                .args(["-0", &p.to_string()])
-                .output()
-                .map_or(false, |o| o.status.success())
-        }
-        #[cfg(not(unix))]
-        {
-            true
-        }
-    });
+    // A zombie has exited and only waits to be reaped; `kill -0` still succeeds on it
+    let is_zombie = stats.as_ref().is_some_and(|stats| stats.is_zombie());
+    let is_responsive = match &stats {
+        Some(_) => !is_zombie,
+        // Nothing to inspect on this platform; trust the process we hold
+        None => cfg!(not(unix)) && pid.is_some(),
+    };
 
     Ok(BotHealth {
+        profile_id: bot.profile_id.clone(),
         is_running,
         is_responsive,
         uptime_seconds,
         pid,
-        memory_mb,
+        memory_mb: stats.as_ref().map(|stats| stats.rss_bytes as f64 / 1024.0 / 1024.0),
+        virtual_memory_mb: stats.as_ref().map(|stats| stats.virtual_bytes as f64 / 1024.0 / 1024.0),
+        cpu_percent: pid.and_then(|pid| tree.cpu_percent_of(pid)),
+        threads: stats.as_ref().and_then(|stats| stats.threads),
+        open_fds: stats.as_ref().and_then(|stats| stats.open_fds),
+        is_zombie,
+        processes: tree.processes,
+        total_memory_mb: tree.total_memory_mb,
+        total_cpu_percent: tree.total_cpu_percent,
     })
 }
 
-#[tauri::command]
-fn get_project_path() -> String {
+// Project path of the default profile
+fn default_project_path() -> String {
     // Try to get from environment variable, fallback to default
     std::env::var("C2ME_PROJECT_PATH")
         .or_else(|_| std::env::var("HOME").map(|h| format!("{}/Project/c2me", h)))
         .unwrap_or_else(|_| "/tmp/c2me".to_string())
 }
 
+#[tauri::command]
+fn get_project_path(profile_id: Option<String>) -> Result<String, String> {
+    profiles::get(&profiles::resolve_id(profile_id)).map(|profile| profile.project_path)
+}
+
 #[tauri::command]
 fn load_config(project_path: String) -> Result<std::collections::HashMap<String, String>, String> {
     let env_path = format!("{}/.env", project_path);
     let content = std::fs::read_to_string(&env_path)
         .map_err(|e| format!("Failed to read .env file: {}", e))?;
 
-    let mut config = std::collections::HashMap::new();
-    for line in content.lines() {
-        let line = line.trim();
-        if line.is_empty() || line.starts_with('#') {
-            continue;
-        }
-        if let Some((key, value)) = line.split_once('=') {
-            config.insert(key.trim().to_string(), value.trim().to_string());
-        }
-    }
-    Ok(config)
+    Ok(environment::parse_dotenv(&content))
 }
 
 #[tauri::command]
 fn save_config(
+    watcher: State<EnvWatcher>,
     project_path: String,
     config: std::collections::HashMap<String, String>,
 ) -> Result<(), String> {
@@ -336,7 +694,9 @@ fn save_config(
         .collect::<Vec<_>>()
         .join("\n");
 
-    std::fs::write(&env_path, content).map_err(|e| format!("Failed to write .env file: {}", e))
+    std::fs::write(&env_path, content).map_err(|e| format!("Failed to write .env file: {}", e))?;
+    watcher.note_write(std::path::Path::new(&env_path));
+    Ok(())
 }
 
 #[tauri::command]
@@ -380,12 +740,13 @@ pub struct AnalyticsSnapshot {
 }
 
 #[tauri::command]
-async fn fetch_analytics() -> Result<serde_json::Value, String> {
-    info!("fetch_analytics: attempting to connect to 127.0.0.1:3002");
+async fn fetch_analytics(profile_id: Option<String>) -> Result<serde_json::Value, String> {
+    let base_url = profiles::get(&profiles::resolve_id(profile_id))?.base_url();
+    info!("fetch_analytics: attempting to connect to {}", base_url);
     let client = get_http_client();
 
     let response = match client
-        .get("http://127.0.0.1:3002/analytics")
+        .get(format!("{}/analytics", base_url))
         .send()
         .await
     {
@@ -410,12 +771,13 @@ async fn fetch_analytics() -> Result<serde_json::Value, String> {
 }
 
 #[tauri::command]
-async fn fetch_metrics() -> Result<BotMetrics, String> {
-    info!("fetch_metrics: attempting to connect to 127.0.0.1:3002");
+async fn fetch_metrics(profile_id: Option<String>) -> Result<BotMetrics, String> {
+    let base_url = profiles::get(&profiles::resolve_id(profile_id))?.base_url();
+    info!("fetch_metrics: attempting to connect to {}", base_url);
     let client = get_http_client();
 
     let response = match client
-        .get("http://127.0.0.1:3002/metrics")
+        .get(format!("{}/metrics", base_url))
         .send()
         .await
     {
@@ -450,12 +812,13 @@ async fn fetch_metrics() -> Result<BotMetrics, String> {
 }
 
 #[tauri::command]
-async fn fetch_extended_metrics() -> Result<serde_json::Value, String> {
-    info!("fetch_extended_metrics: attempting to connect to 127.0.0.1:3002");
+async fn fetch_extended_metrics(profile_id: Option<String>) -> Result<serde_json::Value, String> {
+    let base_url = profiles::get(&profiles::resolve_id(profile_id))?.base_url();
+    info!("fetch_extended_metrics: attempting to connect to {}", base_url);
     let client = get_http_client();
 
     let response = match client
-        .get("http://127.0.0.1:3002/metrics/extended")
+        .get(format!("{}/metrics/extended", base_url))
         .send()
         .await
     {
@@ -653,7 +1016,11 @@ fn mark_setup_complete() -> Result<(), String> {
 }
 
 #[tauri::command]
-fn create_env_file(project_path: String, config: std::collections::HashMap<String, String>) -> Result<(), String> {
+fn create_env_file(
+    watcher: State<EnvWatcher>,
+    project_path: String,
+    config: std::collections::HashMap<String, String>,
+) -> Result<(), String> {
     let env_path = format!("{}/.env", project_path);
 
     // Read existing .env.example if exists
@@ -703,6 +1070,7 @@ STORAGE_TYPE=memory
 
     std::fs::write(&env_path, content)
         .map_err(|e| format!("Failed to write .env file: {}", e))?;
+    watcher.note_write(std::path::Path::new(&env_path));
 
     Ok(())
 }
@@ -848,11 +1216,11 @@ pub fn run() {
             Some(vec!["--minimized"]),
         ))
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        // Log plugin: writes to file, no high-frequency emit
+        // Log plugin: the desktop app's own messages; bot output goes to bot.log via botlog
         .plugin(
             tauri_plugin_log::Builder::new()
                 .target(tauri_plugin_log::Target::new(
-                    tauri_plugin_log::TargetKind::LogDir { file_name: Some("bot.log".into()) },
+                    tauri_plugin_log::TargetKind::LogDir { file_name: Some("desktop".into()) },
                 ))
                 .level(log::LevelFilter::Info)
                 .build(),
@@ -867,7 +1235,18 @@ pub fn run() {
             }
         }))
         .manage(BotState::default())
+        .manage(schedule::Scheduler::default())
+        .manage(EnvWatcher::default())
+        .manage(LiveLog::default())
+        .manage(LogBuffer::default())
         .setup(|app| {
+            // All start/stop/restart requests go through this actor
+            app.manage(BotController::new(app.handle().clone()));
+
+            // Rotating bot.log, next to the plugin's desktop.log
+            let log_dir = app.path().app_log_dir().unwrap_or_else(|_| chatcode_dir().join("logs"));
+            app.manage(BotLog::new(log_dir));
+
             // Start as accessory app (menu bar only, no dock icon)
             #[cfg(target_os = "macos")]
             {
@@ -875,15 +1254,7 @@ pub fn run() {
             }
 
             // Create tray menu items (Chinese, concise style)
-            let dashboard_i = MenuItem::with_id(app, "dashboard", "Dashboard", true, None::<&str>)?;
-            let separator1 = PredefinedMenuItem::separator(app)?;
-            let start_i = MenuItem::with_id(app, "start", "启动", true, None::<&str>)?;
-            let stop_i = MenuItem::with_id(app, "stop", "停止", true, None::<&str>)?;
-            let restart_i = MenuItem::with_id(app, "restart", "重启", true, None::<&str>)?;
-            let separator2 = PredefinedMenuItem::separator(app)?;
-            let quit_i = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
-
-            let menu = Menu::with_items(app, &[&dashboard_i, &separator1, &start_i, &stop_i, &restart_i, &separator2, &quit_i])?;
+            let menu = build_tray_menu(app)?;
 
             // Create tray icon with icon from resources
             let tray = TrayIconBuilder::with_id("main")
@@ -891,66 +1262,45 @@ pub fn run() {
                 .menu(&menu)
                 .show_menu_on_left_click(true)
                 .tooltip("ChatCode Bot • Stopped")
-                .on_menu_event(|app: &AppHandle, event| match event.id.as_ref() {
-                    "quit" => {
-                        app.exit(0);
-                    }
-                    "dashboard" => {
-                        #[cfg(target_os = "macos")]
-                        {
-                            let _ = app.set_activation_policy(tauri::ActivationPolicy::Regular);
+                .on_menu_event(|app: &AppHandle, event| match event.id.as_ref().split_once(':') {
+                    Some(("start", profile_id)) => controller::dispatch(app, profile_id, BotAction::Start, StopReason::Tray, true),
+                    Some(("stop", profile_id)) => controller::dispatch(app, profile_id, BotAction::Stop, StopReason::Tray, true),
+                    Some(("restart", profile_id)) => controller::dispatch(app, profile_id, BotAction::Restart, StopReason::Tray, true),
+                    Some(_) => {}
+                    None => match event.id.as_ref() {
+                        "quit" => {
+                            app.exit(0);
                         }
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    "start" => {
-                        let state: State<BotState> = app.state();
-                        let project_path = get_project_path();
-                        match start_bot_internal(app.clone(), &state, project_path) {
-                            Ok(msg) => {
-                                send_notification(app, "ChatCode Bot", "Bot started successfully");
-                                update_tray_status(app, true, None);
-                                let _ = app.emit("bot-status", msg);
-                            }
-                            Err(e) => { let _ = app.emit("bot-error", e); }
-                        }
-                    }
-                    "stop" => {
-                        let state: State<BotState> = app.state();
-                        match stop_bot_internal(&state) {
-                            Ok(msg) => {
-                                send_notification(app, "ChatCode Bot", "Bot stopped");
-                                update_tray_status(app, false, None);
-                                let _ = app.emit("bot-status", msg);
+                        "dashboard" => {
+                            #[cfg(target_os = "macos")]
+                            {
+                                let _ = app.set_activation_policy(tauri::ActivationPolicy::Regular);
                             }
-                            Err(e) => { let _ = app.emit("bot-error", e); }
-                        }
-                    }
-                    "restart" => {
-                        let state: State<BotState> = app.state();
-                        let project_path = get_project_path();
-                        // Stop first
-                        let _ = stop_bot_internal(&state);
-                        thread::sleep(Duration::from_millis(300));
-                        // Start again
-                        match start_bot_internal(app.clone(), &state, project_path) {
-                            Ok(msg) => {
-                                send_notification(app, "ChatCode Bot", "Bot restarted successfully");
-                                update_tray_status(app, true, None);
-                                let _ = app.emit("bot-status", msg);
+                            if let Some(window) = app.get_webview_window("main") {
+                                let _ = window.show();
+                                let _ = window.set_focus();
                             }
-                            Err(e) => { let _ = app.emit("bot-error", e); }
                         }
-                    }
-                    _ => {}
+                        _ => {}
+                    },
                 })
                 .build(app)?;
 
             // Store tray reference to prevent it from being dropped
             app.manage(tray);
 
+            // Pick up bots left running by a previous session that didn't exit cleanly
+            pidfile::reattach_all(app.handle());
+
+            // Start and stop bots at the boundaries of their run windows
+            schedule::spawn(app.handle().clone());
+
+            // Offer or perform a restart when a running bot's .env changes
+            envwatch::spawn(app.handle().clone());
+
+            // Deliver bot output to the dashboard in batches
+            livelog::spawn(app.handle().clone());
+
             // Show window on first launch (setup not complete)
             let home = std::env::var("HOME").unwrap_or_default();
             let setup_flag = format!("{}/.chatcode/setup_complete", home);
@@ -966,30 +1316,30 @@ pub fn run() {
                     let _ = window.set_focus();
                 }
             } else {
-                // Auto-start bot if setup is complete
+                // Auto-start bots if setup is complete
                 let app_handle = app.handle().clone();
-                std::thread::spawn(move || {
+                tauri::async_runtime::spawn(async move {
                     // Wait a moment for app to fully initialize
-                    std::thread::sleep(std::time::Duration::from_millis(500));
+                    tokio::time::sleep(Duration::from_millis(500)).await;
 
                     let state: State<BotState> = app_handle.state();
-                    let project_path = get_project_path();
-
-                    // Check if bot is already running
-                    let is_running = {
-                        match state.process.lock() {
-                            Ok(process) => process.is_some(),
-                            Err(_) => false,
+                    let controller: State<BotController> = app_handle.state();
+
+                    // Profiles outside their run window are left to the scheduler
+                    for profile in profiles::load_all()
+                        .into_iter()
+                        .filter(|p| p.auto_start && schedule::allows_start(p))
+                    {
+                        // Check if bot is already running
+                        if lifecycle::current(&state.instance(&profile.id)).is_running() {
+                            continue;
                         }
-                    };
-
-                    if !is_running {
-                        match start_bot_internal(app_handle.clone(), &state, project_path) {
+                        match controller.start(&profile.id).await {
                             Ok(_) => {
-                                println!("Bot auto-started successfully");
+                                println!("Bot [{}] auto-started successfully", profile.id);
                             }
                             Err(e) => {
-                                eprintln!("Failed to auto-start bot: {}", e);
+                                eprintln!("Failed to auto-start bot [{}]: {}", profile.id, e);
                             }
                         }
                     }
@@ -1071,40 +1421,9 @@ pub fn run() {
                                 let _ = window.set_focus();
                             }
                         }
-                        "menu_start" => {
-                            let state: State<BotState> = app.state();
-                            let project_path = get_project_path();
-                            match start_bot_internal(app.clone(), &state, project_path) {
-                                Ok(msg) => {
-                                    update_tray_status(app, true, None);
-                                    let _ = app.emit("bot-status", msg);
-                                }
-                                Err(e) => { let _ = app.emit("bot-error", e); }
-                            }
-                        }
-                        "menu_stop" => {
-                            let state: State<BotState> = app.state();
-                            match stop_bot_internal(&state) {
-                                Ok(msg) => {
-                                    update_tray_status(app, false, None);
-                                    let _ = app.emit("bot-status", msg);
-                                }
-                                Err(e) => { let _ = app.emit("bot-error", e); }
-                            }
-                        }
-                        "menu_restart" => {
-                            let state: State<BotState> = app.state();
-                            let project_path = get_project_path();
-                            let _ = stop_bot_internal(&state);
-                            thread::sleep(Duration::from_millis(300));
-                            match start_bot_internal(app.clone(), &state, project_path) {
-                                Ok(msg) => {
-                                    update_tray_status(app, true, None);
-                                    let _ = app.emit("bot-status", msg);
-                                }
-                                Err(e) => { let _ = app.emit("bot-error", e); }
-                            }
-                        }
+                        "menu_start" => controller::dispatch(app, profiles::DEFAULT_PROFILE, BotAction::Start, StopReason::Menu, false),
+                        "menu_stop" => controller::dispatch(app, profiles::DEFAULT_PROFILE, BotAction::Stop, StopReason::Menu, false),
+                        "menu_restart" => controller::dispatch(app, profiles::DEFAULT_PROFILE, BotAction::Restart, StopReason::Menu, false),
                         "menu_logs" => {
                             // Switch to logs tab
                             let _ = app.emit("show-logs", ());
@@ -1125,28 +1444,7 @@ pub fn run() {
                 let shortcut = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyC);
 
                 app.global_shortcut().on_shortcut(shortcut, move |_app, _shortcut, _event| {
-                    let state: State<BotState> = app_handle.state();
-                    let is_running = {
-                        match state.process.lock() {
-                            Ok(process) => process.is_some(),
-                            Err(_) => false,
-                        }
-                    };
-
-                    if is_running {
-                        let _ = stop_bot_internal(&state);
-                        update_tray_status(&app_handle, false, None);
-                        let _ = app_handle.emit("bot-status", "Bot stopped via shortcut");
-                    } else {
-                        let project_path = get_project_path();
-                        match start_bot_internal(app_handle.clone(), &state, project_path) {
-                            Ok(msg) => {
-                                update_tray_status(&app_handle, true, None);
-                                let _ = app_handle.emit("bot-status", msg);
-                            }
-                            Err(e) => { let _ = app_handle.emit("bot-error", e); }
-                        }
-                    }
+                    controller::dispatch(&app_handle, profiles::DEFAULT_PROFILE, BotAction::Toggle, StopReason::Shortcut, false);
                 })?;
             }
 
@@ -1177,7 +1475,35 @@ pub fn run() {
             get_bot_install_path,
             is_bot_extracted,
             extract_bot_bundle,
-            detect_claude_code_path
+            detect_claude_code_path,
+            // Supervisor commands
+            supervisor::get_supervisor_config,
+            supervisor::set_supervisor_config,
+            // Launch mode commands
+            launch::get_launch_mode,
+            launch::set_launch_mode,
+            // Profile commands
+            profiles::list_profiles,
+            profiles::save_profile,
+            profiles::delete_profile,
+            // Environment commands
+            environment::get_resolved_env,
+            secrets::list_secrets,
+            secrets::set_secret,
+            secrets::delete_secret,
+            // Schedule commands
+            schedule::get_next_scheduled_action,
+            schedule::set_schedule_override,
+            schedule::clear_schedule_override,
+            // Run history commands
+            history::list_runs,
+            history::get_availability,
+            // Log commands
+            logbuffer::query_logs,
+            botlog::list_log_files,
+            botlog::read_log_file,
+            botlog::list_run_logs,
+            botlog::open_run_log
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
@@ -1198,9 +1524,25 @@ pub fn run() {
                     }
                 }
                 RunEvent::Exit => {
-                    // Clean up bot process before exit
+                    // Clean up bot processes before exit (directly: the async runtime is going away)
+                    // All bots are stopped at once with the full grace period, so quitting
+                    // waits for the slowest rather than for the sum of them
                     let state: State<BotState> = app.state();
-                    let _ = stop_bot_internal(&state);
+                    thread::scope(|scope| {
+                        for bot in state.instances() {
+                            // Bots the user started by hand keep running after we quit
+                            let external = bot
+                                .process
+                                .lock()
+                                .is_ok_and(|process| process.as_ref().is_some_and(BotProcess::is_external));
+                            if external {
+                                continue;
+                            }
+                            scope.spawn(move || {
+                                let _ = stop_bot_internal(app, &bot, StopReason::AppExit);
+                            });
+                        }
+                    });
                 }
                 _ => {}
             }
@@ -0,0 +1,87 @@
+// Bot lifecycle state machine.
+//
+// Every start, stop, crash and automatic restart goes through `transition`,
+// which rejects illegal moves and broadcasts the change as `bot-lifecycle`.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use log::info;
+
+use crate::BotInstance;
+
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BotPhase {
+    Stopped,
+    // Process spawned, not yet serving
+    Starting,
+    Ready,
+    Stopping,
+    // Exited on its own; may be followed by an automatic restart
+    Crashed,
+    // Waiting out the restart delay after a crash
+    Backoff,
+}
+
+impl BotPhase {
+    pub fn can_transition_to(self, next: BotPhase) -> bool {
+        use BotPhase::*;
+        matches!(
+            (self, next),
+            (Stopped, Starting)
+                // Adopting a bot that was started outside the app
+                | (Stopped, Ready)
+                | (Starting, Ready)
+                | (Starting, Stopping)
+                | (Starting, Crashed)
+                | (Starting, Stopped)
+                | (Ready, Stopping)
+                | (Ready, Crashed)
+                | (Stopping, Stopped)
+                | (Crashed, Backoff)
+                | (Crashed, Starting)
+                | (Crashed, Stopped)
+                | (Backoff, Starting)
+                | (Backoff, Stopped)
+        )
+    }
+
+    // Whether a bot process exists for this phase
+    pub fn is_running(self) -> bool {
+        matches!(self, BotPhase::Starting | BotPhase::Ready | BotPhase::Stopping)
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct LifecycleEvent {
+    profile_id: String,
+    from: BotPhase,
+    to: BotPhase,
+    at: String,
+}
+
+pub fn current(bot: &BotInstance) -> BotPhase {
+    bot.phase.lock().map_or(BotPhase::Stopped, |phase| *phase)
+}
+
+// Move to `to` if the current phase allows it. Returns the previous phase.
+pub fn transition(app: &AppHandle, bot: &BotInstance, to: BotPhase) -> Result<BotPhase, String> {
+    let from = {
+        let mut phase = bot.phase.lock().map_err(|e| e.to_string())?;
+        let from = *phase;
+        if !from.can_transition_to(to) {
+            return Err(format!("Cannot go from {:?} to {:?}", from, to));
+        }
+        *phase = to;
+        from
+    };
+
+    info!("Bot lifecycle [{}]: {:?} -> {:?}", bot.profile_id, from, to);
+    let _ = app.emit("bot-lifecycle", LifecycleEvent {
+        profile_id: bot.profile_id.clone(),
+        from,
+        to,
+        at: chrono::Local::now().to_rfc3339(),
+    });
+    Ok(from)
+}
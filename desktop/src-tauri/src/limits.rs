@@ -0,0 +1,280 @@
+// Resource limits for the spawned bot.
+//
+// rlimits and the nice value are set in the child between fork and exec, so
+// they are inherited by node and every claude session. rlimits apply per
+// process; a cap on the whole tree needs a cgroup v2 group, which is created
+// next to the app's own cgroup when the hierarchy is delegated to the user.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use log::{info, warn};
+
+// Per-profile limits; everything is off by default
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    // RLIMIT_AS per process. V8 reserves a lot of address space up front,
+    // so keep this well above the expected heap size.
+    pub max_address_space_mb: Option<u64>,
+    // RLIMIT_NOFILE per process
+    pub max_open_files: Option<u64>,
+    // Scheduling priority, -20 (highest) to 19; lowering it needs privileges
+    pub nice: Option<i32>,
+    // memory.max of the bot's cgroup, for the whole tree
+    pub cgroup_memory_mb: Option<u64>,
+    // cpu.max of the bot's cgroup, in percent of one core
+    pub cgroup_cpu_percent: Option<u32>,
+}
+
+// Which limit killed the bot
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    // The cgroup's memory.max triggered the OOM killer
+    CgroupMemory,
+    // An allocation failed against RLIMIT_AS
+    AddressSpace,
+}
+
+// Limits in effect for one run
+#[derive(Clone, Default)]
+pub struct AppliedLimits {
+    limits: ResourceLimits,
+    cgroup: Option<PathBuf>,
+    // oom_kill count of the cgroup when the run started
+    oom_kills: u64,
+}
+
+impl AppliedLimits {
+    // Drop the cgroup if the spawned bot did not end up in it, so no cap is
+    // reported or attributed that does not apply
+    pub fn confirm_cgroup(&mut self, profile_id: &str, pid: u32) {
+        let Some(dir) = &self.cgroup else {
+            return;
+        };
+        if in_cgroup(pid, dir) {
+            info!("Bot [{}] runs in cgroup {}", profile_id, dir.display());
+        } else {
+            warn!(
+                "Bot [{}] could not be moved into cgroup {}, its cgroup limits do not apply",
+                profile_id,
+                dir.display()
+            );
+            self.cgroup = None;
+        }
+    }
+
+    // Attribute an exit to a limit, if one was hit
+    pub fn exceeded(&self, status: Option<ExitStatus>, stderr_tail: &[String]) -> Option<LimitKind> {
+        if let Some(cgroup) = &self.cgroup {
+            if oom_kills(cgroup) > self.oom_kills {
+                return Some(LimitKind::CgroupMemory);
+            }
+        }
+
+        // Node aborts with "Allocation failed" or "out of memory" when mmap is refused
+        let failed = status.is_some_and(|status| !status.success());
+        let out_of_memory = stderr_tail
+            .iter()
+            .any(|line| line.contains("Allocation failed") || line.contains("out of memory"));
+        if self.limits.max_address_space_mb.is_some() && failed && out_of_memory {
+            return Some(LimitKind::AddressSpace);
+        }
+        None
+    }
+}
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+// A sibling of the app's own cgroup; the app's group holds a process, so
+// controllers can't be enabled below it
+fn cgroup_dir(profile_id: &str) -> Option<PathBuf> {
+    let own = std::fs::read_to_string("/proc/self/cgroup").ok()?;
+    // The unified (v2) hierarchy is the "0::" entry
+    let own = own.lines().find_map(|line| line.strip_prefix("0::"))?;
+    let parent = match Path::new(own.trim_start_matches('/')).parent() {
+        Some(parent) => Path::new(CGROUP_ROOT).join(parent),
+        // The app runs in the root group
+        None => PathBuf::from(CGROUP_ROOT),
+    };
+    Some(parent.join(format!("chatcode-{}", profile_id)))
+}
+
+// Whether `pid` is a member of the cgroup at `dir`
+fn in_cgroup(pid: u32, dir: &Path) -> bool {
+    std::fs::read_to_string(format!("/proc/{}/cgroup", pid))
+        .ok()
+        .and_then(|cgroup| {
+            let own = cgroup.lines().find_map(|line| line.strip_prefix("0::"))?;
+            Some(Path::new(CGROUP_ROOT).join(own.trim().trim_start_matches('/')) == dir)
+        })
+        .unwrap_or(false)
+}
+
+fn oom_kills(cgroup: &Path) -> u64 {
+    std::fs::read_to_string(cgroup.join("memory.events"))
+        .ok()
+        .and_then(|events| {
+            events
+                .lines()
+                .find_map(|line| line.strip_prefix("oom_kill "))?
+                .trim()
+                .parse()
+                .ok()
+        })
+        .unwrap_or(0)
+}
+
+fn prepare_cgroup(profile_id: &str, limits: &ResourceLimits) -> Result<PathBuf, String> {
+    let dir = cgroup_dir(profile_id).ok_or("cgroup v2 is not available")?;
+    let parent = dir.parent().unwrap_or(Path::new(CGROUP_ROOT));
+    let controllers = std::fs::read_to_string(parent.join("cgroup.subtree_control"))
+        .map_err(|e| format!("cgroup v2 is not available: {}", e))?;
+    let missing = |controller: &str| !controllers.split_whitespace().any(|c| c == controller);
+    if limits.cgroup_memory_mb.is_some() && missing("memory") {
+        return Err("memory controller is not delegated".to_string());
+    }
+    if limits.cgroup_cpu_percent.is_some() && missing("cpu") {
+        return Err("cpu controller is not delegated".to_string());
+    }
+
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    // Written every launch so a removed limit doesn't linger in a reused group
+    let memory_max = limits
+        .cgroup_memory_mb
+        .map_or("max".to_string(), |mb| (mb * 1024 * 1024).to_string());
+    let cpu_max = limits
+        .cgroup_cpu_percent
+        .map_or("max 100000".to_string(), |percent| format!("{} 100000", percent as u64 * 1000));
+    for (file, value) in [("memory.max", memory_max), ("cpu.max", cpu_max)] {
+        let path = dir.join(file);
+        if path.exists() {
+            std::fs::write(&path, value).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+    }
+    Ok(dir)
+}
+
+// Lower the soft and hard limit of `resource` to `value`, never above the current hard limit
+#[cfg(unix)]
+fn lower_rlimit(resource: i32, value: u64) -> std::io::Result<()> {
+    let mut current = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    unsafe {
+        if libc::getrlimit(resource as _, &mut current) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let value = (value as libc::rlim_t).min(current.rlim_max);
+        let limit = libc::rlimit { rlim_cur: value, rlim_max: value };
+        if libc::setrlimit(resource as _, &limit) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+// Whether this process may set its nice value to `nice`. Raising it is always
+// allowed; lowering it needs root or, on Linux, a high enough RLIMIT_NICE.
+#[cfg(unix)]
+fn can_set_nice(nice: i32) -> bool {
+    unsafe {
+        if libc::geteuid() == 0 || nice >= libc::getpriority(libc::PRIO_PROCESS as _, 0) {
+            return true;
+        }
+        #[cfg(target_os = "linux")]
+        {
+            let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+            // RLIMIT_NICE is stored as 20 - nice
+            if libc::getrlimit(libc::RLIMIT_NICE, &mut limit) == 0 && (20 - nice) as libc::rlim_t <= limit.rlim_cur {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+// Limits of a bot reattached after a desktop restart: the profile's, and the
+// cgroup when the bot is still found in it
+pub fn reattached(profile_id: &str, pid: u32, limits: &ResourceLimits) -> AppliedLimits {
+    let cgroup = cgroup_dir(profile_id).filter(|dir| in_cgroup(pid, dir));
+    AppliedLimits {
+        limits: limits.clone(),
+        oom_kills: cgroup.as_deref().map_or(0, oom_kills),
+        cgroup,
+    }
+}
+
+// Configure `command` so the child starts under `limits`
+pub fn apply(command: &mut Command, profile_id: &str, limits: &ResourceLimits) -> AppliedLimits {
+    let wants_cgroup = limits.cgroup_memory_mb.is_some() || limits.cgroup_cpu_percent.is_some();
+    let cgroup = if wants_cgroup && cfg!(target_os = "linux") {
+        match prepare_cgroup(profile_id, limits) {
+            Ok(dir) => Some(dir),
+            Err(e) => {
+                warn!("Bot [{}] cgroup limits skipped: {}", profile_id, e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    #[cfg(unix)]
+    {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+        use std::os::unix::process::CommandExt;
+
+        let address_space = limits.max_address_space_mb.map(|mb| mb * 1024 * 1024);
+        let open_files = limits.max_open_files;
+        // A failing setpriority would fail the whole launch with a bare EACCES
+        let nice = limits.nice.filter(|&nice| {
+            let allowed = can_set_nice(nice);
+            if !allowed {
+                warn!("Bot [{}] nice value {} skipped: lowering it needs privileges", profile_id, nice);
+            }
+            allowed
+        });
+        // Built up front: nothing may allocate between fork and exec
+        let cgroup_procs = cgroup
+            .as_ref()
+            .and_then(|dir| CString::new(dir.join("cgroup.procs").as_os_str().as_bytes()).ok());
+
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(bytes) = address_space {
+                    lower_rlimit(libc::RLIMIT_AS as i32, bytes)?;
+                }
+                if let Some(files) = open_files {
+                    lower_rlimit(libc::RLIMIT_NOFILE as i32, files)?;
+                }
+                if let Some(nice) = nice {
+                    if libc::setpriority(libc::PRIO_PROCESS as _, 0, nice) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                // Writing "0" moves the writing process; descendants follow.
+                // Failing here would fail the launch with a bare errno, so
+                // `confirm_cgroup` checks the outcome after the spawn instead.
+                if let Some(procs) = &cgroup_procs {
+                    let fd = libc::open(procs.as_ptr(), libc::O_WRONLY);
+                    if fd >= 0 {
+                        libc::write(fd, b"0".as_ptr().cast(), 1);
+                        libc::close(fd);
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+    #[cfg(not(unix))]
+    if limits.max_address_space_mb.is_some() || limits.max_open_files.is_some() || limits.nice.is_some() {
+        warn!("Bot [{}] resource limits are not supported on this platform", profile_id);
+    }
+
+    AppliedLimits {
+        limits: limits.clone(),
+        oom_kills: cgroup.as_deref().map_or(0, oom_kills),
+        cgroup,
+    }
+}
@@ -0,0 +1,123 @@
+// Live bot output for the dashboard.
+//
+// Emitting every line would flood the webview, so the reader threads push
+// parsed entries into a bounded queue and a flusher thread emits them as
+// `bot-log-batch` events, every FLUSH_INTERVAL or as soon as BATCH_LINES are
+// waiting. Nothing is queued while the main window is hidden, and lines that
+// do not fit are dropped; the count is reported with the next batch so the UI
+// knows it missed some and can fall back to bot.log.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::logs::LogEntry;
+
+// Longest a line waits before it is emitted
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+// Lines per event; a full batch is flushed right away
+const BATCH_LINES: usize = 200;
+
+// Lines waiting for the flusher before new ones are dropped
+const QUEUE_CAPACITY: usize = 5000;
+
+#[derive(Clone, Serialize)]
+pub struct LogBatch {
+    entries: Vec<LogEntry>,
+    // Lines not delivered since the previous batch
+    dropped: u64,
+}
+
+#[derive(Default)]
+struct Queue {
+    entries: VecDeque<LogEntry>,
+    dropped: u64,
+}
+
+#[derive(Default)]
+pub struct LiveLog {
+    queue: Mutex<Queue>,
+    full_batch: Condvar,
+    // Whether the main window is shown, refreshed by the flusher
+    visible: AtomicBool,
+}
+
+impl LiveLog {
+    // Called by the reader threads for every line; never blocks on the UI
+    pub fn push(&self, entry: LogEntry) {
+        let Ok(mut queue) = self.queue.lock() else {
+            return;
+        };
+        if !self.visible.load(Ordering::Relaxed) || queue.entries.len() >= QUEUE_CAPACITY {
+            queue.dropped += 1;
+            return;
+        }
+        queue.entries.push_back(entry);
+        if queue.entries.len() >= BATCH_LINES {
+            self.full_batch.notify_one();
+        }
+    }
+
+    // Wait until a batch is full or the flush interval has passed
+    fn wait(&self) {
+        let Ok(queue) = self.queue.lock() else {
+            return;
+        };
+        let _ = self
+            .full_batch
+            .wait_timeout_while(queue, FLUSH_INTERVAL, |queue| queue.entries.len() < BATCH_LINES);
+    }
+
+    // Queued lines are stale by the time the window is shown again
+    fn discard(&self) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.dropped += queue.entries.len() as u64;
+            queue.entries.clear();
+        }
+    }
+
+    fn take_batch(&self) -> Option<LogBatch> {
+        let mut queue = self.queue.lock().ok()?;
+        if queue.entries.is_empty() && queue.dropped == 0 {
+            return None;
+        }
+        let count = queue.entries.len().min(BATCH_LINES);
+        let batch = LogBatch {
+            entries: queue.entries.drain(..count).collect(),
+            dropped: queue.dropped,
+        };
+        queue.dropped = 0;
+        Some(batch)
+    }
+}
+
+fn window_visible(app: &AppHandle) -> bool {
+    app.get_webview_window("main").is_some_and(|window| {
+        window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false)
+    })
+}
+
+pub fn spawn(app: AppHandle) {
+    thread::spawn(move || {
+        let live: State<LiveLog> = app.state();
+        loop {
+            live.wait();
+
+            let visible = window_visible(&app);
+            live.visible.store(visible, Ordering::Relaxed);
+            // The drop count is kept for when the window comes back
+            if !visible {
+                live.discard();
+                continue;
+            }
+            if let Some(batch) = live.take_batch() {
+                let _ = app.emit("bot-log-batch", batch);
+            }
+        }
+    });
+}
@@ -0,0 +1,253 @@
+// Recent bot output kept in memory.
+//
+// The reader threads append every parsed line to a ring buffer holding the
+// last `log_buffer_lines` entries of all profiles. `query_logs` filters it by
+// level, stream, pattern and time range and pages backwards through it with
+// the sequence number of the oldest entry returned, so the Logs tab no longer
+// has to re-read and re-parse bot.log.
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::logs::{LogEntry, LogLevel, LogStream};
+use crate::profiles;
+
+// Page size when the query does not set one
+const DEFAULT_LIMIT: usize = 200;
+
+#[derive(Clone, Serialize)]
+pub struct BufferedEntry {
+    // Increases by one per line, across profiles
+    seq: u64,
+    #[serde(flatten)]
+    entry: LogEntry,
+}
+
+#[derive(Default)]
+struct Ring {
+    entries: VecDeque<BufferedEntry>,
+    next_seq: u64,
+}
+
+#[derive(Default)]
+pub struct LogBuffer {
+    ring: Mutex<Ring>,
+}
+
+impl LogBuffer {
+    // Append `entry`, dropping the oldest entries beyond `capacity`
+    pub fn push(&self, entry: LogEntry, capacity: usize) {
+        let Ok(mut ring) = self.ring.lock() else {
+            return;
+        };
+        let seq = ring.next_seq;
+        ring.next_seq += 1;
+        ring.entries.push_back(BufferedEntry { seq, entry });
+        while ring.entries.len() > capacity {
+            ring.entries.pop_front();
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct LogQuery {
+    profile_id: Option<String>,
+    // Entries at this level or above
+    min_level: Option<LogLevel>,
+    stream: Option<LogStream>,
+    // Regular expression matched against the message
+    pattern: Option<String>,
+    // RFC 3339, inclusive
+    from: Option<String>,
+    // RFC 3339, exclusive
+    to: Option<String>,
+    // `next_cursor` of the previous page; None starts at the newest entry
+    cursor: Option<u64>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct LogPage {
+    // Newest first
+    entries: Vec<BufferedEntry>,
+    // Pass back as `cursor` for older entries; None when there are no more
+    next_cursor: Option<u64>,
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Local>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|at| at.with_timezone(&Local))
+        .map_err(|e| format!("Invalid time {}: {}", value, e))
+}
+
+impl LogBuffer {
+    // One page of entries matching `query`, newest first
+    fn query(&self, query: LogQuery) -> Result<LogPage, String> {
+        let profile_id = profiles::resolve_id(query.profile_id);
+        let pattern = query
+            .pattern
+            .as_deref()
+            .filter(|pattern| !pattern.is_empty())
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| format!("Invalid pattern: {}", e))?;
+        let from = query.from.as_deref().map(parse_time).transpose()?;
+        let to = query.to.as_deref().map(parse_time).transpose()?;
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).max(1);
+
+        let matches = |entry: &LogEntry| {
+            if entry.profile_id != profile_id
+                || query.min_level.is_some_and(|level| entry.level < level)
+                || query.stream.is_some_and(|stream| entry.stream != stream)
+            {
+                return false;
+            }
+            if from.is_some() || to.is_some() {
+                let Ok(at) = parse_time(&entry.timestamp) else {
+                    return false;
+                };
+                if from.is_some_and(|from| at < from) || to.is_some_and(|to| at >= to) {
+                    return false;
+                }
+            }
+            pattern.as_ref().is_none_or(|pattern| pattern.is_match(&entry.message))
+        };
+
+        let ring = self.ring.lock().map_err(|e| e.to_string())?;
+        let mut entries = Vec::new();
+        let mut next_cursor = None;
+        for buffered in ring.entries.iter().rev() {
+            if query.cursor.is_some_and(|cursor| buffered.seq >= cursor) {
+                continue;
+            }
+            if !matches(&buffered.entry) {
+                continue;
+            }
+            if entries.len() == limit {
+                // Only hand out a cursor when another page exists
+                next_cursor = entries.last().map(|last: &BufferedEntry| last.seq);
+                break;
+            }
+            entries.push(buffered.clone());
+        }
+
+        Ok(LogPage { entries, next_cursor })
+    }
+}
+
+#[tauri::command]
+pub fn query_logs(buffer: tauri::State<LogBuffer>, query: LogQuery) -> Result<LogPage, String> {
+    buffer.query(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(profile_id: &str, level: LogLevel, minute: u32) -> LogEntry {
+        LogEntry {
+            profile_id: profile_id.to_string(),
+            level,
+            message: format!("line at minute {}", minute),
+            timestamp: format!("2026-05-04T12:{:02}:00+00:00", minute),
+            stream: LogStream::Stdout,
+        }
+    }
+
+    // `count` entries of the default profile, one a minute, with seq 0..count
+    fn buffer(count: u32) -> LogBuffer {
+        let buffer = LogBuffer::default();
+        for minute in 0..count {
+            buffer.push(entry(profiles::DEFAULT_PROFILE, LogLevel::Info, minute), 100);
+        }
+        buffer
+    }
+
+    fn page(buffer: &LogBuffer, query: LogQuery) -> (Vec<u64>, Option<u64>) {
+        let page = buffer.query(query).expect("query succeeds");
+        (page.entries.iter().map(|buffered| buffered.seq).collect(), page.next_cursor)
+    }
+
+    fn paged(limit: usize, cursor: Option<u64>) -> LogQuery {
+        LogQuery {
+            limit: Some(limit),
+            cursor,
+            ..LogQuery::default()
+        }
+    }
+
+    #[test]
+    fn pages_backwards_from_the_newest_entry() {
+        let buffer = buffer(5);
+        assert_eq!(page(&buffer, paged(2, None)), (vec![4, 3], Some(3)));
+        assert_eq!(page(&buffer, paged(2, Some(3))), (vec![2, 1], Some(1)));
+        assert_eq!(page(&buffer, paged(2, Some(1))), (vec![0], None));
+    }
+
+    #[test]
+    fn last_full_page_has_no_cursor() {
+        // The second page ends exactly at the oldest entry
+        let buffer = buffer(4);
+        assert_eq!(page(&buffer, paged(2, None)), (vec![3, 2], Some(2)));
+        assert_eq!(page(&buffer, paged(2, Some(2))), (vec![1, 0], None));
+    }
+
+    #[test]
+    fn everything_on_one_page_has_no_cursor() {
+        assert_eq!(page(&buffer(3), paged(3, None)), (vec![2, 1, 0], None));
+        assert_eq!(page(&buffer(0), paged(3, None)), (vec![], None));
+    }
+
+    #[test]
+    fn capacity_drops_the_oldest_entries() {
+        let buffer = LogBuffer::default();
+        for minute in 0..5 {
+            buffer.push(entry(profiles::DEFAULT_PROFILE, LogLevel::Info, minute), 3);
+        }
+        assert_eq!(page(&buffer, paged(10, None)), (vec![4, 3, 2], None));
+    }
+
+    #[test]
+    fn time_range_includes_from_and_excludes_to() {
+        let query = LogQuery {
+            from: Some("2026-05-04T12:01:00+00:00".to_string()),
+            to: Some("2026-05-04T12:03:00+00:00".to_string()),
+            ..LogQuery::default()
+        };
+        assert_eq!(page(&buffer(5), query), (vec![2, 1], None));
+    }
+
+    #[test]
+    fn cursor_skips_entries_filtered_out() {
+        let buffer = LogBuffer::default();
+        for (profile_id, level) in [
+            (profiles::DEFAULT_PROFILE, LogLevel::Error),
+            ("other", LogLevel::Error),
+            (profiles::DEFAULT_PROFILE, LogLevel::Debug),
+            (profiles::DEFAULT_PROFILE, LogLevel::Warn),
+            (profiles::DEFAULT_PROFILE, LogLevel::Error),
+        ] {
+            buffer.push(entry(profile_id, level, 0), 100);
+        }
+        let warnings = |cursor| LogQuery {
+            min_level: Some(LogLevel::Warn),
+            ..paged(1, cursor)
+        };
+        assert_eq!(page(&buffer, warnings(None)), (vec![4], Some(4)));
+        assert_eq!(page(&buffer, warnings(Some(4))), (vec![3], Some(3)));
+        assert_eq!(page(&buffer, warnings(Some(3))), (vec![0], None));
+    }
+
+    #[test]
+    fn rejects_an_invalid_pattern() {
+        let query = LogQuery {
+            pattern: Some("(".to_string()),
+            ..LogQuery::default()
+        };
+        assert!(buffer(1).query(query).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+// Structured bot output.
+//
+// The bot logs through pino: one JSON object per line in production, and
+// pino-pretty's `[HH:MM:ss.l] INFO (pid): msg` in development. Older code and
+// dependencies print plain text or `[LEVEL] msg`. Every line of stdout and
+// stderr is turned into a `LogEntry` with its real level and timestamp; lines
+// that match none of these keep the stream's default level.
+
+use chrono::{DateTime, Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "trace" | "verbose" => Some(LogLevel::Trace),
+            "debug" => Some(LogLevel::Debug),
+            "info" | "log" | "notice" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "error" | "err" => Some(LogLevel::Error),
+            "fatal" | "critical" | "crit" => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
+
+    // pino's numeric levels
+    fn from_number(level: u64) -> Self {
+        match level {
+            0..=10 => LogLevel::Trace,
+            11..=20 => LogLevel::Debug,
+            21..=30 => LogLevel::Info,
+            31..=40 => LogLevel::Warn,
+            41..=50 => LogLevel::Error,
+            _ => LogLevel::Fatal,
+        }
+    }
+
+    pub fn as_log(self) -> log::Level {
+        match self {
+            LogLevel::Trace => log::Level::Trace,
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Error | LogLevel::Fatal => log::Level::Error,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Clone, Serialize)]
+pub struct LogEntry {
+    pub profile_id: String,
+    pub level: LogLevel,
+    pub message: String,
+    // RFC 3339; the bot's own timestamp when the line carries one
+    pub timestamp: String,
+    pub stream: LogStream,
+}
+
+// pino-pretty colorizes its output
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            out.push(c);
+            continue;
+        }
+        // CSI sequences end with a byte in @..~
+        if chars.peek() == Some(&'[') {
+            chars.next();
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+// Full RFC 3339 timestamps, epoch milliseconds, or a time of day from today
+fn parse_timestamp(value: &str) -> Option<DateTime<Local>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Some(at.with_timezone(&Local));
+    }
+    let time = NaiveTime::parse_from_str(value, "%H:%M:%S%.f").ok()?;
+    Local::now().date_naive().and_time(time).and_local_timezone(Local).single()
+}
+
+fn json_timestamp(value: &Value) -> Option<DateTime<Local>> {
+    match value {
+        Value::String(value) => parse_timestamp(value),
+        Value::Number(millis) => {
+            DateTime::from_timestamp_millis(millis.as_i64()?).map(|at| at.with_timezone(&Local))
+        }
+        _ => None,
+    }
+}
+
+struct Parsed {
+    level: Option<LogLevel>,
+    message: String,
+    timestamp: Option<DateTime<Local>>,
+}
+
+// {"level":30,"time":"...","msg":"..."} and similar
+fn parse_json(line: &str) -> Option<Parsed> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let Value::Object(object) = serde_json::from_str::<Value>(line).ok()? else {
+        return None;
+    };
+    let level = match object.get("level") {
+        Some(Value::Number(level)) => level.as_u64().map(LogLevel::from_number),
+        Some(Value::String(level)) => LogLevel::from_name(level),
+        _ => None,
+    };
+    let timestamp = ["time", "timestamp"]
+        .iter()
+        .find_map(|key| object.get(*key).and_then(json_timestamp));
+    let text = |key: &str| object.get(key).and_then(Value::as_str).map(str::to_string);
+    let mut message = text("msg")
+        .or_else(|| text("message"))
+        .unwrap_or_else(|| line.to_string());
+    // logError puts the error under `err`
+    if let Some(err) = object.get("err").and_then(|err| err.get("message")).and_then(Value::as_str) {
+        message = format!("{}: {}", message, err);
+    }
+    Some(Parsed { level, message, timestamp })
+}
+
+// `[12:34:56.789] INFO (1234): msg`, `[INFO] msg`, `WARN: msg` and combinations
+fn parse_prefixed(line: &str) -> Option<Parsed> {
+    let mut rest = line.trim_start();
+    let mut level = None;
+    let mut timestamp = None;
+
+    // Up to two bracketed fields, each a level or a timestamp
+    for _ in 0..2 {
+        let Some(inner) = rest.strip_prefix('[') else {
+            break;
+        };
+        let Some((field, after)) = inner.split_once(']') else {
+            break;
+        };
+        if let Some(found) = LogLevel::from_name(field.trim()) {
+            level = Some(found);
+        } else if let Some(found) = parse_timestamp(field.trim()) {
+            timestamp = Some(found);
+        } else {
+            break;
+        }
+        rest = after.trim_start();
+    }
+
+    // pino-pretty puts the level after the time, followed by the pid or a colon
+    if level.is_none() {
+        let end = rest.find([':', ' ']).unwrap_or(rest.len());
+        if let Some(found) = LogLevel::from_name(&rest[..end]) {
+            let after = rest[end..].trim_start();
+            let after = match after.strip_prefix('(') {
+                Some(pid) => pid.split_once(')').map_or(after, |(_, after)| after),
+                None => after,
+            };
+            if let Some(message) = after.strip_prefix(':') {
+                level = Some(found);
+                rest = message.trim_start();
+            }
+        }
+    }
+
+    if level.is_none() && timestamp.is_none() {
+        return None;
+    }
+    Some(Parsed { level, message: rest.to_string(), timestamp })
+}
+
+// Parses one stream of a run; lines of a multi-line message such as a stack
+// trace keep the level of the line that started it
+pub struct LogParser {
+    profile_id: String,
+    stream: LogStream,
+    last_level: Option<LogLevel>,
+}
+
+impl LogParser {
+    pub fn new(profile_id: &str, stream: LogStream) -> Self {
+        LogParser {
+            profile_id: profile_id.to_string(),
+            stream,
+            last_level: None,
+        }
+    }
+
+    fn default_level(&self) -> LogLevel {
+        match self.stream {
+            LogStream::Stdout => LogLevel::Info,
+            LogStream::Stderr => LogLevel::Error,
+        }
+    }
+
+    pub fn parse(&mut self, line: &str) -> LogEntry {
+        let line = strip_ansi(line);
+        let trimmed = line.trim_end();
+        let parsed = parse_json(trimmed).or_else(|| parse_prefixed(trimmed));
+
+        let (level, message, timestamp) = match parsed {
+            Some(parsed) => {
+                let level = parsed.level.unwrap_or_else(|| self.default_level());
+                self.last_level = Some(level);
+                (level, parsed.message, parsed.timestamp)
+            }
+            None => {
+                let continuation = trimmed.starts_with(char::is_whitespace) || trimmed.is_empty();
+                let level = match self.last_level {
+                    Some(level) if continuation => level,
+                    _ => {
+                        self.last_level = None;
+                        self.default_level()
+                    }
+                };
+                (level, trimmed.to_string(), None)
+            }
+        };
+
+        LogEntry {
+            profile_id: self.profile_id.clone(),
+            level,
+            message,
+            timestamp: timestamp.unwrap_or_else(Local::now).to_rfc3339(),
+            stream: self.stream,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(stream: LogStream, line: &str) -> LogEntry {
+        LogParser::new("main", stream).parse(line)
+    }
+
+    fn local_time(entry: &LogEntry) -> DateTime<Local> {
+        DateTime::parse_from_rfc3339(&entry.timestamp)
+            .expect("timestamp is RFC 3339")
+            .with_timezone(&Local)
+    }
+
+    #[test]
+    fn parses_pino_json() {
+        let entry = parse(LogStream::Stdout, r#"{"level":40,"time":1700000000000,"pid":1,"msg":"slow reply"}"#);
+        assert_eq!(entry.level, LogLevel::Warn);
+        assert_eq!(entry.message, "slow reply");
+        assert_eq!(local_time(&entry).timestamp_millis(), 1_700_000_000_000);
+    }
+
+    #[test]
+    fn appends_error_message_of_json_line() {
+        let entry = parse(
+            LogStream::Stdout,
+            r#"{"level":"error","msg":"Request failed","err":{"message":"socket hang up"}}"#,
+        );
+        assert_eq!(entry.level, LogLevel::Error);
+        assert_eq!(entry.message, "Request failed: socket hang up");
+    }
+
+    #[test]
+    fn keeps_json_without_message_whole() {
+        let line = r#"{"level":30,"event":"tick"}"#;
+        let entry = parse(LogStream::Stdout, line);
+        assert_eq!(entry.level, LogLevel::Info);
+        assert_eq!(entry.message, line);
+    }
+
+    #[test]
+    fn parses_colorized_pino_pretty() {
+        let entry = parse(
+            LogStream::Stdout,
+            "\u{1b}[90m[12:34:56.789]\u{1b}[39m \u{1b}[33mWARN\u{1b}[39m (4242): Rate limited",
+        );
+        assert_eq!(entry.level, LogLevel::Warn);
+        assert_eq!(entry.message, "Rate limited");
+        // Today at that time, whatever the local offset
+        assert!(entry.timestamp.contains("T12:34:56.789"), "{}", entry.timestamp);
+    }
+
+    #[test]
+    fn parses_bracketed_and_colon_levels() {
+        let entry = parse(LogStream::Stdout, "[ERROR] Polling stopped");
+        assert_eq!((entry.level, entry.message.as_str()), (LogLevel::Error, "Polling stopped"));
+
+        let entry = parse(LogStream::Stderr, "warning: deprecated option");
+        assert_eq!((entry.level, entry.message.as_str()), (LogLevel::Warn, "deprecated option"));
+    }
+
+    #[test]
+    fn plain_lines_take_the_stream_default() {
+        assert_eq!(parse(LogStream::Stdout, "Listening on 3000").level, LogLevel::Info);
+        assert_eq!(parse(LogStream::Stderr, "Listening on 3000").level, LogLevel::Error);
+        // A word that only looks like a level is not one
+        assert_eq!(parse(LogStream::Stdout, "info about the bot").level, LogLevel::Info);
+        assert_eq!(parse(LogStream::Stderr, "error handling enabled").message, "error handling enabled");
+    }
+
+    #[test]
+    fn continuation_lines_keep_the_previous_level() {
+        let mut parser = LogParser::new("main", LogStream::Stdout);
+        assert_eq!(parser.parse("[ERROR] Unhandled rejection").level, LogLevel::Error);
+        assert_eq!(parser.parse("    at handler (bot.js:10:5)").level, LogLevel::Error);
+        assert_eq!(parser.parse("").level, LogLevel::Error);
+        // The next unindented line starts over
+        assert_eq!(parser.parse("Reconnected").level, LogLevel::Info);
+        assert_eq!(parser.parse("    indented again").level, LogLevel::Info);
+    }
+
+    #[test]
+    fn keeps_stream_and_profile() {
+        let entry = LogParser::new("work", LogStream::Stderr).parse("[INFO] ok");
+        assert_eq!(entry.profile_id, "work");
+        assert_eq!(entry.stream, LogStream::Stderr);
+    }
+}
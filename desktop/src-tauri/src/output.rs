@@ -0,0 +1,179 @@
+// Bot stdout and stderr on disk.
+//
+// The bot writes its output straight into files under ~/.chatcode/output
+// rather than into pipes to the desktop app: a pipe dies with its reader, and
+// the bot's next write after the app is killed would fail with EPIPE. The app
+// tails the files instead and, after a restart, picks up those of a
+// reattached bot again at their end. The files hold the output before it is
+// masked, so they are only readable by the user, are emptied whenever the app
+// has read everything and the bot has gone quiet, and are deleted at startup
+// when no bot of ours is left running.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use log::error;
+
+use crate::chatcode_dir;
+use crate::logs::LogStream;
+use crate::BotInstance;
+
+const OUTPUT_DIR: &str = "output";
+
+// How often a tail checks for new output
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+fn path(profile_id: &str, stream: LogStream) -> PathBuf {
+    let extension = match stream {
+        LogStream::Stdout => "stdout",
+        LogStream::Stderr => "stderr",
+    };
+    chatcode_dir().join(OUTPUT_DIR).join(format!("{}.{}", profile_id, extension))
+}
+
+fn open_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+}
+
+// A fresh file for one stream of a launch: the end the bot writes to and ours
+// to tail. A previous run still being drained keeps reading its unlinked file.
+pub fn create(profile_id: &str, stream: LogStream) -> Result<(File, File), String> {
+    let path = path(profile_id, stream);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create output directory: {}", e))?;
+    }
+    match std::fs::remove_file(&path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to remove previous bot output: {}", e)),
+    }
+    let writer = open_options()
+        .create_new(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to create bot output file: {}", e))?;
+    // Write access is needed to empty it
+    let reader = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open bot output file: {}", e))?;
+    Ok((writer, reader))
+}
+
+// Output left behind by a bot that is no longer running
+pub fn remove(profile_id: &str) {
+    for stream in [LogStream::Stdout, LogStream::Stderr] {
+        let _ = std::fs::remove_file(path(profile_id, stream));
+    }
+}
+
+// The file of a reattached bot, positioned at its end; output written while
+// the app was not running is not replayed
+pub fn resume(profile_id: &str, stream: LogStream) -> Option<File> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path(profile_id, stream))
+        .ok()?;
+    file.seek(SeekFrom::End(0)).ok()?;
+    Some(file)
+}
+
+// Whether anything can still write to the files: the bot's process group on
+// unix, elsewhere the launched process as long as it is still managed
+fn writers_alive(bot: &BotInstance, pgid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        let _ = bot;
+        crate::process::group_alive(pgid)
+    }
+    #[cfg(not(unix))]
+    {
+        bot.process
+            .lock()
+            .is_ok_and(|process| process.as_ref().is_some_and(|process| process.pgid() == pgid))
+    }
+}
+
+// Drop what was read, unless more output arrived since. The bot appends, so
+// its next write starts over at the beginning.
+fn empty(file: &mut File, read_to: u64) -> std::io::Result<()> {
+    if file.metadata()?.len() != read_to {
+        return Ok(());
+    }
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(())
+}
+
+// Hand every complete line appended to `file` to `on_line` until the bot's
+// process group `pgid` is gone and the file is drained, then empty it
+pub fn tail(mut file: File, bot: &BotInstance, pgid: u32, mut on_line: impl FnMut(String)) {
+    let mut buffer = [0u8; 8192];
+    let mut pending = Vec::new();
+    // Set once the group is gone; the next read that comes back empty is the last
+    let mut exited = false;
+    // Nothing was written during the last poll
+    let mut idle = false;
+
+    loop {
+        let read = match file.read(&mut buffer) {
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                error!("Failed to read bot output: {}", e);
+                break;
+            }
+        };
+        if read == 0 {
+            if exited {
+                break;
+            }
+            exited = !writers_alive(bot, pgid);
+            if exited {
+                continue;
+            }
+            // Only once the bot is quiet, so a line written between the size
+            // check and the truncation is unlikely
+            if idle {
+                match file.stream_position() {
+                    Ok(0) => {}
+                    Ok(read_to) => {
+                        if let Err(e) = empty(&mut file, read_to) {
+                            error!("Failed to clear bot output: {}", e);
+                        }
+                    }
+                    Err(e) => error!("Failed to read bot output position: {}", e),
+                }
+            }
+            idle = true;
+            thread::sleep(POLL_INTERVAL);
+            continue;
+        }
+        idle = false;
+
+        pending.extend_from_slice(&buffer[..read]);
+        while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line[..end]);
+            on_line(line.trim_end_matches('\r').to_string());
+        }
+    }
+
+    // A last line without a newline
+    if !pending.is_empty() {
+        on_line(String::from_utf8_lossy(&pending).trim_end_matches('\r').to_string());
+    }
+    if let Err(e) = file.set_len(0) {
+        error!("Failed to clear bot output: {}", e);
+    }
+}
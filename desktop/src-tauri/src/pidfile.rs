@@ -0,0 +1,180 @@
+// Per-profile lock file for the spawned bot.
+//
+// Written on every launch to ~/.chatcode/bot-<profile>.lock and removed when
+// the bot stops or exits. A lock left behind means the desktop app died
+// without cleaning up; on the next launch the recorded process is reattached
+// if it is still the same bot, so auto-start doesn't spawn a second one that
+// fights it for the Telegram token, and its output files are tailed again.
+
+use serde::{Deserialize, Serialize};
+#[cfg(unix)]
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use log::{info, warn};
+
+use crate::logs::LogStream;
+use crate::profiles::{self, BotProfile};
+use crate::redact::Redactor;
+use crate::{adopt, capture_output, chatcode_dir, environment, limits, output, procfs, BotInstance, BotState};
+
+#[derive(Serialize, Deserialize)]
+struct BotLock {
+    pid: u32,
+    // Process start in clock ticks since boot (Linux); tells a reused PID apart
+    start_ticks: Option<u64>,
+    started_at: String,
+    command: Vec<String>,
+}
+
+impl BotLock {
+    // Whether the recorded process is still alive and still the bot we launched
+    #[cfg(unix)]
+    fn is_live(&self) -> bool {
+        if !crate::process::pid_alive(self.pid) {
+            return false;
+        }
+        // The bot was spawned as leader of its own process group
+        if crate::process::pgid_of(self.pid) != Some(self.pid) {
+            return false;
+        }
+        let same_start = match (self.start_ticks, procfs::start_ticks(self.pid)) {
+            (Some(recorded), Some(current)) => recorded == current,
+            // No start time on this platform; the command has to tell
+            _ => true,
+        };
+        same_start && self.runs_command()
+    }
+
+    // Whether the process still runs the recorded command. Compared word by
+    // word, as `ps` on macOS splits arguments at spaces: the program shows up
+    // by file name, possibly behind an interpreter (`node .../pnpm.cjs`), and
+    // the arguments end the command line.
+    #[cfg(unix)]
+    fn runs_command(&self) -> bool {
+        let Some(current) = procfs::cmdline(self.pid) else {
+            return false;
+        };
+        let words = |args: &[String]| -> Vec<String> {
+            args.iter().flat_map(|arg| arg.split_whitespace()).map(str::to_string).collect()
+        };
+        let stem = |word: &str| Path::new(word).file_stem().map(|stem| stem.to_os_string());
+        let Some((program, args)) = self.command.split_first() else {
+            return false;
+        };
+        let (current, args) = (words(&current), words(args));
+        let Some(program_end) = current.len().checked_sub(args.len()) else {
+            return false;
+        };
+        current[program_end..] == args[..]
+            && stem(program).is_some()
+            && current[..program_end].iter().any(|word| stem(word) == stem(program))
+    }
+
+    #[cfg(not(unix))]
+    fn is_live(&self) -> bool {
+        false
+    }
+
+    fn age(&self) -> Option<Duration> {
+        procfs::process_age(self.pid).or_else(|| {
+            let started = chrono::DateTime::parse_from_rfc3339(&self.started_at).ok()?;
+            chrono::Local::now().signed_duration_since(started).to_std().ok()
+        })
+    }
+}
+
+fn path(profile_id: &str) -> PathBuf {
+    chatcode_dir().join(format!("bot-{}.lock", profile_id))
+}
+
+// Record a freshly spawned bot
+pub fn write(profile_id: &str, pid: u32, command: &Command) -> Result<(), String> {
+    let lock = BotLock {
+        pid,
+        start_ticks: procfs::start_ticks(pid),
+        started_at: chrono::Local::now().to_rfc3339(),
+        command: std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect(),
+    };
+
+    std::fs::create_dir_all(chatcode_dir())
+        .map_err(|e| format!("Failed to create .chatcode directory: {}", e))?;
+    let content = serde_json::to_string_pretty(&lock)
+        .map_err(|e| format!("Failed to serialize bot lock: {}", e))?;
+    std::fs::write(path(profile_id), content).map_err(|e| format!("Failed to write bot lock: {}", e))
+}
+
+pub fn remove(profile_id: &str) {
+    let _ = std::fs::remove_file(path(profile_id));
+}
+
+fn read(profile_id: &str) -> Option<BotLock> {
+    let content = std::fs::read_to_string(path(profile_id)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+// Pick the output files of a reattached bot up again, masked with the
+// profile's current secrets
+fn resume_output(
+    app: &AppHandle,
+    bot: &Arc<BotInstance>,
+    profile: &BotProfile,
+    pgid: u32,
+) -> Option<JoinHandle<()>> {
+    let stdout = output::resume(&bot.profile_id, LogStream::Stdout)?;
+    let stderr = output::resume(&bot.profile_id, LogStream::Stderr)?;
+    let redactor = match environment::resolve(profile) {
+        Ok(environment) => Arc::new(Redactor::new(&environment)),
+        Err(e) => {
+            warn!("Not capturing output of bot [{}]: {}", bot.profile_id, e);
+            return None;
+        }
+    };
+    Some(capture_output(app, bot, pgid, stdout, stderr, None, redactor))
+}
+
+fn reattach(app: &AppHandle, bot: &Arc<BotInstance>, profile: &BotProfile) {
+    // Without a live bot nothing reads its output anymore; unmasked, it must not stay on disk
+    let Some(lock) = read(&bot.profile_id) else {
+        output::remove(&bot.profile_id);
+        return;
+    };
+    if !lock.is_live() {
+        info!("Removing stale lock for bot [{}] (PID {})", bot.profile_id, lock.pid);
+        remove(&bot.profile_id);
+        output::remove(&bot.profile_id);
+        return;
+    }
+
+    let stderr_reader = resume_output(app, bot, profile, lock.pid);
+    match adopt::attach(app, bot, lock.pid, lock.pid, lock.age(), false, stderr_reader) {
+        Ok(()) => {
+            // So a crash can still be put down to a limit
+            if let Ok(mut applied) = bot.limits.lock() {
+                *applied = limits::reattached(&profile.id, lock.pid, &profile.limits);
+            }
+            info!(
+                "Reattached bot [{}] with PID {} ({})",
+                bot.profile_id,
+                lock.pid,
+                lock.command.join(" ")
+            )
+        }
+        Err(e) => warn!("Failed to reattach bot [{}]: {}", bot.profile_id, e),
+    }
+}
+
+// Called once at startup, before any profile is auto-started
+pub fn reattach_all(app: &AppHandle) {
+    let state = app.state::<BotState>();
+    for profile in profiles::load_all() {
+        reattach(app, &state.instance(&profile.id), &profile);
+    }
+}
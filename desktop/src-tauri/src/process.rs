@@ -0,0 +1,169 @@
+// Process-group helpers for the bot.
+//
+// The bot is spawned as the leader of its own process group, so `pnpm`,
+// the `node` bot under it and any `claude` subprocesses can be signalled
+// together instead of orphaning everything below the wrapper.
+
+use serde::Serialize;
+use std::process::{Child, Command, ExitStatus};
+use std::time::Duration;
+#[cfg(unix)]
+use std::{thread, time::Instant};
+
+// How the bot ended up stopping
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum StopMethod {
+    // Exited on its own within the grace period after SIGTERM
+    Graceful,
+    // Grace period expired and the group was SIGKILLed
+    Killed,
+    // Nothing was running; a pending automatic restart was called off
+    Cancelled,
+}
+
+// The bot's root process
+pub enum BotProcess {
+    Spawned(Child),
+    // Not spawned in this session: reattached from our lock file, or, when
+    // `external`, started by someone else and found through its listening port
+    Adopted { pid: u32, pgid: u32, external: bool },
+}
+
+impl BotProcess {
+    pub fn id(&self) -> u32 {
+        match self {
+            BotProcess::Spawned(child) => child.id(),
+            BotProcess::Adopted { pid, .. } => *pid,
+        }
+    }
+
+    // Process group signalled on stop; a spawned bot leads its own
+    pub fn pgid(&self) -> u32 {
+        match self {
+            BotProcess::Spawned(child) => child.id(),
+            BotProcess::Adopted { pgid, .. } => *pgid,
+        }
+    }
+
+    // Exit status of a spawned child that has been reaped; unknown for adopted processes
+    pub fn exit_status(&mut self) -> Option<ExitStatus> {
+        match self {
+            BotProcess::Spawned(child) => child.try_wait().ok().flatten(),
+            BotProcess::Adopted { .. } => None,
+        }
+    }
+
+    pub fn is_adopted(&self) -> bool {
+        matches!(self, BotProcess::Adopted { .. })
+    }
+
+    // Started by someone else, so left running when the app quits
+    pub fn is_external(&self) -> bool {
+        matches!(self, BotProcess::Adopted { external: true, .. })
+    }
+
+    // Reap the leader if it exited; adopted processes are reaped by their own parent
+    fn reap(&mut self) {
+        if let BotProcess::Spawned(child) = self {
+            let _ = child.try_wait();
+        }
+    }
+}
+
+// Make the spawned child the leader of a new process group (pgid == pid)
+pub fn isolate(command: &mut Command) -> &mut Command {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command
+}
+
+// Send `signal` to every process in the group. Returns false if the group is gone.
+#[cfg(unix)]
+pub fn signal_group(pgid: u32, signal: libc::c_int) -> bool {
+    unsafe { libc::kill(-(pgid as libc::pid_t), signal) == 0 }
+}
+
+// Whether a single process still exists
+#[cfg(unix)]
+pub fn pid_alive(pid: u32) -> bool {
+    if unsafe { libc::kill(pid as libc::pid_t, 0) } == 0 {
+        return true;
+    }
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+// Process group of `pid`, if it still exists
+#[cfg(unix)]
+pub fn pgid_of(pid: u32) -> Option<u32> {
+    let pgid = unsafe { libc::getpgid(pid as libc::pid_t) };
+    (pgid > 0).then_some(pgid as u32)
+}
+
+// Process group the desktop app itself runs in
+#[cfg(unix)]
+pub fn own_pgid() -> u32 {
+    unsafe { libc::getpgrp() as u32 }
+}
+
+// Whether any process is left in the group (zombies included until reaped)
+#[cfg(unix)]
+pub fn group_alive(pgid: u32) -> bool {
+    if unsafe { libc::kill(-(pgid as libc::pid_t), 0) } == 0 {
+        return true;
+    }
+    // EPERM means the group exists but belongs to someone else
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+// Poll until the group is empty or the timeout expires
+#[cfg(unix)]
+pub fn wait_group_exit(pgid: u32, timeout: Duration) -> bool {
+    let start = Instant::now();
+    while group_alive(pgid) {
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    true
+}
+
+// Ask the bot to shut down and give it `grace` to exit before SIGKILL.
+// A spawned child is always reaped before returning.
+pub fn terminate(process: &mut BotProcess, grace: Duration) -> StopMethod {
+    let pgid = process.pgid();
+
+    #[cfg(unix)]
+    {
+        signal_group(pgid, libc::SIGTERM);
+
+        let start = Instant::now();
+        loop {
+            // Reap the leader as soon as it exits so the group check can succeed
+            process.reap();
+            if !group_alive(pgid) {
+                return StopMethod::Graceful;
+            }
+            if start.elapsed() >= grace {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        signal_group(pgid, libc::SIGKILL);
+    }
+
+    // No SIGTERM equivalent elsewhere; kill() is the only option
+    #[cfg(not(unix))]
+    let _ = (pgid, grace);
+
+    if let BotProcess::Spawned(child) = process {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    StopMethod::Killed
+}
@@ -0,0 +1,370 @@
+// Process inspection: listening sockets, process identity and resource usage.
+//
+// On Linux everything is read from /proc. macOS has no /proc, so the
+// listening PID is looked up with `lsof` and statistics come from `ps`.
+
+#[cfg(target_os = "linux")]
+use std::collections::HashSet;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// Socket inodes listening on `port` (IPv4 and IPv6)
+#[cfg(target_os = "linux")]
+fn listening_inodes(port: u16) -> HashSet<u64> {
+    // TCP_LISTEN in the `st` column
+    const LISTEN: &str = "0A";
+
+    let mut inodes = HashSet::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let Ok(content) = std::fs::read_to_string(table) else {
+            continue;
+        };
+        // sl local_address rem_address st tx:rx tr:when retrnsmt uid timeout inode
+        for line in content.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != LISTEN {
+                continue;
+            }
+            let local_port = fields[1]
+                .rsplit(':')
+                .next()
+                .and_then(|hex| u16::from_str_radix(hex, 16).ok());
+            if local_port != Some(port) {
+                continue;
+            }
+            if let Ok(inode) = fields[9].parse::<u64>() {
+                if inode != 0 {
+                    inodes.insert(inode);
+                }
+            }
+        }
+    }
+    inodes
+}
+
+// PID of the process listening on `port` on this machine
+#[cfg(target_os = "linux")]
+pub fn listening_pid(port: u16) -> Option<u32> {
+    let inodes = listening_inodes(port);
+    if inodes.is_empty() {
+        return None;
+    }
+
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        // Processes of other users can't be inspected; skip them
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|t| t.strip_prefix("socket:["))
+                .and_then(|t| t.strip_suffix(']'))
+                .and_then(|t| t.parse::<u64>().ok());
+            if inode.is_some_and(|inode| inodes.contains(&inode)) {
+                return Some(pid);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(target_os = "macos")]
+pub fn listening_pid(port: u16) -> Option<u32> {
+    let output = std::process::Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-t"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn listening_pid(_port: u16) -> Option<u32> {
+    None
+}
+
+// Fields of /proc/<pid>/stat after the command name, starting at `state`
+#[cfg(target_os = "linux")]
+fn stat_fields(pid: u32) -> Option<Vec<String>> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name may contain spaces and parentheses; it ends at the last ')'
+    let rest = &stat[stat.rfind(')')? + 1..];
+    Some(rest.split_whitespace().map(str::to_string).collect())
+}
+
+#[cfg(target_os = "linux")]
+fn clock_ticks() -> f64 {
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        ticks if ticks > 0 => ticks as f64,
+        _ => 100.0,
+    }
+}
+
+// When the process started, in clock ticks since boot. Unlike the PID this
+// is never reused, so it identifies one specific process.
+#[cfg(target_os = "linux")]
+pub fn start_ticks(pid: u32) -> Option<u64> {
+    // `starttime` is field 22 of stat
+    stat_fields(pid)?.get(19)?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn start_ticks(_pid: u32) -> Option<u64> {
+    None
+}
+
+// How long the process has been running
+#[cfg(target_os = "linux")]
+pub fn process_age(pid: u32) -> Option<Duration> {
+    let start_ticks = start_ticks(pid)? as f64;
+    let uptime: f64 = std::fs::read_to_string("/proc/uptime")
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    let age = uptime - start_ticks / clock_ticks();
+    (age >= 0.0).then(|| Duration::from_secs_f64(age))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn process_age(_pid: u32) -> Option<Duration> {
+    None
+}
+
+// Parent PID of every process on the system
+#[cfg(target_os = "linux")]
+fn parent_pids() -> HashMap<u32, u32> {
+    let mut parents = HashMap::new();
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return parents;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        // `ppid` is field 4 of stat
+        if let Some(ppid) = stat_fields(pid).and_then(|fields| fields.get(1)?.parse().ok()) {
+            parents.insert(pid, ppid);
+        }
+    }
+    parents
+}
+
+#[cfg(target_os = "macos")]
+fn parent_pids() -> HashMap<u32, u32> {
+    let Ok(output) = std::process::Command::new("ps").args(["-axo", "pid=,ppid="]).output() else {
+        return HashMap::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some((fields.next()?.parse().ok()?, fields.next()?.parse().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn parent_pids() -> HashMap<u32, u32> {
+    HashMap::new()
+}
+
+// `root` and every process below it as (pid, parent pid), parents before children
+pub fn process_tree(root: u32) -> Vec<(u32, u32)> {
+    let parents = parent_pids();
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (&pid, &ppid) in &parents {
+        children.entry(ppid).or_default().push(pid);
+    }
+
+    let mut tree = vec![(root, parents.get(&root).copied().unwrap_or(0))];
+    let mut next = 0;
+    while next < tree.len() {
+        let pid = tree[next].0;
+        if let Some(below) = children.get_mut(&pid) {
+            below.sort_unstable();
+            tree.extend(below.iter().map(|&child| (child, pid)));
+        }
+        next += 1;
+    }
+    tree
+}
+
+// Arguments the process was started with
+#[cfg(target_os = "linux")]
+pub fn cmdline(pid: u32) -> Option<Vec<String>> {
+    let raw = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<String> = raw
+        .split(|&byte| byte == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    // Zombies and kernel threads have an empty command line; fall back to the name
+    if args.is_empty() {
+        let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+        return Some(vec![comm.trim().to_string()]);
+    }
+    Some(args)
+}
+
+// `ps` only gives the joined command, so arguments containing spaces are split
+#[cfg(target_os = "macos")]
+pub fn cmdline(pid: u32) -> Option<Vec<String>> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "command=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let args: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    (!args.is_empty()).then_some(args)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn cmdline(_pid: u32) -> Option<Vec<String>> {
+    None
+}
+
+// Working directory, which tells Claude sessions for different projects apart
+#[cfg(target_os = "linux")]
+pub fn cwd(pid: u32) -> Option<String> {
+    std::fs::read_link(format!("/proc/{}/cwd", pid))
+        .ok()
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn cwd(_pid: u32) -> Option<String> {
+    None
+}
+
+// Resource usage of a single process
+pub struct ProcessStats {
+    // One-letter state as shown by `ps` (R, S, D, Z, ...)
+    pub state: char,
+    // User plus system CPU time consumed so far
+    pub cpu_time: Duration,
+    pub rss_bytes: u64,
+    pub virtual_bytes: u64,
+    pub threads: Option<u32>,
+    pub open_fds: Option<u32>,
+}
+
+impl ProcessStats {
+    pub fn is_zombie(&self) -> bool {
+        self.state == 'Z'
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn stats(pid: u32) -> Option<ProcessStats> {
+    let fields = stat_fields(pid)?;
+    let state = fields.first()?.chars().next()?;
+    // `utime` and `stime` are fields 14 and 15 of stat
+    let ticks = fields.get(11)?.parse::<u64>().ok()? + fields.get(12)?.parse::<u64>().ok()?;
+
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let status_value = |key: &str| -> Option<u64> {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(key))?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    };
+
+    Some(ProcessStats {
+        state,
+        cpu_time: Duration::from_secs_f64(ticks as f64 / clock_ticks()),
+        // A zombie has released its memory and has no Vm* lines
+        rss_bytes: status_value("VmRSS:").unwrap_or(0) * 1024,
+        virtual_bytes: status_value("VmSize:").unwrap_or(0) * 1024,
+        threads: status_value("Threads:").map(|threads| threads as u32),
+        open_fds: std::fs::read_dir(format!("/proc/{}/fd", pid))
+            .ok()
+            .map(|fds| fds.count() as u32),
+    })
+}
+
+// `ps` CPU time: [dd-][hh:]mm:ss.ss
+#[cfg(target_os = "macos")]
+fn parse_cpu_time(time: &str) -> Option<Duration> {
+    let (days, time) = match time.split_once('-') {
+        Some((days, time)) => (days.parse::<f64>().ok()?, time),
+        None => (0.0, time),
+    };
+    let mut secs = days * 86_400.0;
+    for (part, unit) in time.rsplit(':').zip([1.0, 60.0, 3_600.0]) {
+        secs += part.parse::<f64>().ok()? * unit;
+    }
+    Some(Duration::from_secs_f64(secs))
+}
+
+#[cfg(target_os = "macos")]
+pub fn stats(pid: u32) -> Option<ProcessStats> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "state=,rss=,vsz=,time=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    let mut fields = output.split_whitespace();
+
+    let state = fields.next()?.chars().next()?;
+    let rss_kb: u64 = fields.next()?.parse().ok()?;
+    let virtual_kb: u64 = fields.next()?.parse().ok()?;
+    let cpu_time = parse_cpu_time(fields.next()?)?;
+
+    Some(ProcessStats {
+        state,
+        cpu_time,
+        rss_bytes: rss_kb * 1024,
+        virtual_bytes: virtual_kb * 1024,
+        threads: None,
+        open_fds: None,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn stats(_pid: u32) -> Option<ProcessStats> {
+    None
+}
+
+// CPU time readings from the previous poll, per PID. CPU percent is
+// measured over the interval between two polls.
+#[derive(Default)]
+pub struct CpuSampler {
+    samples: HashMap<u32, (Instant, Duration)>,
+}
+
+impl CpuSampler {
+    // Percent of one core used by `pid` since its last sample; None on the first
+    pub fn percent(&mut self, pid: u32, cpu_time: Duration) -> Option<f64> {
+        let now = Instant::now();
+        let previous = self.samples.insert(pid, (now, cpu_time))?;
+        let wall = now.duration_since(previous.0).as_secs_f64();
+        if wall <= 0.0 {
+            return None;
+        }
+        let used = cpu_time.saturating_sub(previous.1).as_secs_f64();
+        Some(used / wall * 100.0)
+    }
+
+    // Forget PIDs that are no longer measured
+    pub fn retain(&mut self, pids: &[u32]) {
+        self.samples.retain(|pid, _| pids.contains(pid));
+    }
+}
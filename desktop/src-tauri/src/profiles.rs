@@ -0,0 +1,149 @@
+// Named bot profiles.
+//
+// Each profile is one bot instance with its own project directory, extra
+// environment and metrics port, persisted in ~/.chatcode/profiles.json.
+// The `default` profile always exists and mirrors `get_project_path`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+use crate::lifecycle;
+use crate::limits::ResourceLimits;
+use crate::schedule::Schedule;
+use crate::{chatcode_dir, default_project_path, rebuild_tray_menu, BotState};
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+// Metrics/health port the bot listens on unless METRICS_PORT says otherwise
+pub const DEFAULT_PORT: u16 = 3002;
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+fn default_auto_start() -> bool {
+    true
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BotProfile {
+    pub id: String,
+    pub name: String,
+    pub project_path: String,
+    // Extra variables passed to the bot on top of its .env
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    // Start this profile when the desktop app launches
+    #[serde(default = "default_auto_start")]
+    pub auto_start: bool,
+    #[serde(default)]
+    pub limits: ResourceLimits,
+    // Weekly run windows; the bot runs whenever it likes while disabled
+    #[serde(default)]
+    pub schedule: Schedule,
+}
+
+impl BotProfile {
+    fn default_profile() -> Self {
+        Self {
+            id: DEFAULT_PROFILE.to_string(),
+            name: "Default".to_string(),
+            project_path: default_project_path(),
+            env: HashMap::new(),
+            port: DEFAULT_PORT,
+            auto_start: true,
+            limits: ResourceLimits::default(),
+            schedule: Schedule::default(),
+        }
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+}
+
+fn store_path() -> PathBuf {
+    chatcode_dir().join("profiles.json")
+}
+
+// All profiles, default first
+pub fn load_all() -> Vec<BotProfile> {
+    let mut profiles: Vec<BotProfile> = std::fs::read_to_string(store_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    if !profiles.iter().any(|p| p.id == DEFAULT_PROFILE) {
+        profiles.insert(0, BotProfile::default_profile());
+    }
+    profiles
+}
+
+fn save_all(profiles: &[BotProfile]) -> Result<(), String> {
+    std::fs::create_dir_all(chatcode_dir())
+        .map_err(|e| format!("Failed to create .chatcode directory: {}", e))?;
+    let content = serde_json::to_string_pretty(profiles)
+        .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+    std::fs::write(store_path(), content).map_err(|e| format!("Failed to write profiles: {}", e))
+}
+
+pub fn get(profile_id: &str) -> Result<BotProfile, String> {
+    load_all()
+        .into_iter()
+        .find(|p| p.id == profile_id)
+        .ok_or_else(|| format!("Unknown profile: {}", profile_id))
+}
+
+// Commands take an optional profile id; omitting it targets the default profile
+pub fn resolve_id(profile_id: Option<String>) -> String {
+    profile_id.unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+#[tauri::command]
+pub fn list_profiles() -> Vec<BotProfile> {
+    load_all()
+}
+
+// Ids end up in file names, cgroup names and tray item ids (`start:<id>`)
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[tauri::command]
+pub fn save_profile(app: AppHandle, profile: BotProfile) -> Result<(), String> {
+    if !is_valid_id(&profile.id) {
+        return Err("Profile id may only contain letters, digits, '_' and '-'".to_string());
+    }
+
+    let mut profiles = load_all();
+    if profiles.iter().any(|p| p.id != profile.id && p.port == profile.port) {
+        return Err(format!("Port {} is already used by another profile", profile.port));
+    }
+    match profiles.iter_mut().find(|p| p.id == profile.id) {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+    save_all(&profiles)?;
+    rebuild_tray_menu(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, state: State<BotState>, profile_id: String) -> Result<(), String> {
+    if profile_id == DEFAULT_PROFILE {
+        return Err("The default profile cannot be deleted".to_string());
+    }
+    if lifecycle::current(&state.instance(&profile_id)).is_running() {
+        return Err("Stop the bot before deleting its profile".to_string());
+    }
+
+    let mut profiles = load_all();
+    profiles.retain(|p| p.id != profile_id);
+    save_all(&profiles)?;
+    state.remove_instance(&profile_id);
+    rebuild_tray_menu(&app);
+    Ok(())
+}
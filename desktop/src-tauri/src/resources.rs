@@ -0,0 +1,96 @@
+// Resource usage of the bot's whole process tree.
+//
+// Most memory goes to the `claude` CLI subprocesses the bot starts per
+// session, not to the `pnpm` wrapper we hold. Every process below the managed
+// one is measured and tagged with a short command name so a heavy session
+// stands out.
+
+use serde::Serialize;
+use std::path::Path;
+
+use crate::procfs::{self, CpuSampler};
+
+#[derive(Clone, Serialize)]
+pub struct ProcessUsage {
+    pid: u32,
+    parent_pid: u32,
+    // Short tag: node, claude, git, pnpm, ...
+    command: String,
+    cmdline: String,
+    cwd: Option<String>,
+    memory_mb: f64,
+    // None on the first poll of a process
+    cpu_percent: Option<f64>,
+    is_zombie: bool,
+}
+
+#[derive(Clone, Serialize, Default)]
+pub struct TreeUsage {
+    pub processes: Vec<ProcessUsage>,
+    pub total_memory_mb: f64,
+    pub total_cpu_percent: f64,
+}
+
+impl TreeUsage {
+    pub fn cpu_percent_of(&self, pid: u32) -> Option<f64> {
+        self.processes.iter().find(|process| process.pid == pid)?.cpu_percent
+    }
+}
+
+fn to_mb(bytes: u64) -> f64 {
+    bytes as f64 / 1024.0 / 1024.0
+}
+
+fn base_name(arg: &str) -> &str {
+    Path::new(arg).file_name().and_then(|name| name.to_str()).unwrap_or(arg)
+}
+
+// Claude Code runs as `node .../claude-code/cli.js`, so the script path is
+// checked as well as the program
+fn command_name(args: &[String]) -> String {
+    if args.iter().take(2).any(|arg| arg.contains("claude")) {
+        return "claude".to_string();
+    }
+    args.first().map_or_else(|| "?".to_string(), |program| base_name(program).to_string())
+}
+
+// Measure `root` and all of its descendants. The sampler keeps CPU readings
+// between polls and forgets processes that have gone away.
+pub fn measure(root: u32, sampler: &mut CpuSampler) -> TreeUsage {
+    let tree = procfs::process_tree(root);
+    let pids: Vec<u32> = tree.iter().map(|&(pid, _)| pid).collect();
+    sampler.retain(&pids);
+
+    let mut usage = TreeUsage::default();
+    for (pid, parent_pid) in tree {
+        // Exited between listing and measuring
+        let Some(stats) = procfs::stats(pid) else {
+            continue;
+        };
+        let args = procfs::cmdline(pid).unwrap_or_default();
+        let cpu_percent = sampler.percent(pid, stats.cpu_time);
+
+        usage.total_memory_mb += to_mb(stats.rss_bytes);
+        usage.total_cpu_percent += cpu_percent.unwrap_or(0.0);
+        usage.processes.push(ProcessUsage {
+            pid,
+            parent_pid,
+            command: command_name(&args),
+            cmdline: args.join(" "),
+            cwd: procfs::cwd(pid),
+            memory_mb: to_mb(stats.rss_bytes),
+            cpu_percent,
+            is_zombie: stats.is_zombie(),
+        });
+    }
+    usage
+}
+
+// Resident memory of `root` and its descendants, without the per-process detail
+pub fn tree_memory_mb(root: u32) -> f64 {
+    procfs::process_tree(root)
+        .into_iter()
+        .filter_map(|(pid, _)| procfs::stats(pid))
+        .map(|stats| to_mb(stats.rss_bytes))
+        .sum()
+}
@@ -0,0 +1,522 @@
+// Scheduled run windows per profile.
+//
+// A profile's schedule lists weekly windows during which the bot should run.
+// The scheduler starts or stops the bot through the controller at launch and
+// whenever the schedule crosses a window boundary. A manual override pins the
+// bot running or stopped until the next boundary, after which the schedule
+// applies again.
+
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use log::{error, info};
+
+use crate::controller::BotController;
+use crate::history::StopReason;
+use crate::lifecycle::{self, BotPhase};
+use crate::profiles::{self, BotProfile};
+use crate::BotState;
+
+// How often the scheduler checks for boundaries
+const TICK_INTERVAL: Duration = Duration::from_secs(15);
+
+// How far ahead the next boundary is searched for
+const LOOKAHEAD_DAYS: i64 = 8;
+
+// The bot runs from `start` to `end` on each of `days`. A window whose end is
+// not after its start runs past midnight into the next day.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RunWindow {
+    // ISO weekdays, 1 = Monday .. 7 = Sunday
+    pub days: Vec<u32>,
+    // "HH:MM", local time
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Schedule {
+    pub enabled: bool,
+    pub windows: Vec<RunWindow>,
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M").ok()
+}
+
+// The instant the clock shows `at` in `tz`. A time repeated by a DST change is
+// taken at its first occurrence for a start and its last for an end, so a
+// window covers the repeated hour both times; a skipped one an hour later, past the gap.
+fn local_instant<Tz: TimeZone>(tz: &Tz, at: NaiveDateTime, first: bool) -> Option<DateTime<Tz>> {
+    match tz.from_local_datetime(&at) {
+        LocalResult::Single(instant) => Some(instant),
+        LocalResult::Ambiguous(a, b) => {
+            // `Local` lists them in either order, and at the end of the repeated
+            // hour also one where the clock shows an hour less
+            let shown = [a, b]
+                .into_iter()
+                .filter(|instant| tz.from_utc_datetime(&instant.naive_utc()).naive_local() == at);
+            if first {
+                shown.min()
+            } else {
+                shown.max()
+            }
+        }
+        LocalResult::None => tz.from_local_datetime(&(at + ChronoDuration::hours(1))).earliest(),
+    }
+}
+
+impl RunWindow {
+    // Start and end of the window's occurrence beginning on `date`, if it runs that day
+    fn occurrence<Tz: TimeZone>(&self, tz: &Tz, date: NaiveDate) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
+        if !self.days.contains(&date.weekday().number_from_monday()) {
+            return None;
+        }
+        let (start, end) = (parse_time(&self.start)?, parse_time(&self.end)?);
+        let end_date = if end <= start { date.succ_opt()? } else { date };
+        let start = local_instant(tz, date.and_time(start), true)?;
+        let end = local_instant(tz, end_date.and_time(end), false)?;
+        Some((start, end))
+    }
+}
+
+// The schedule is read in the time zone of the instants it is given, the local
+// one everywhere but in tests
+impl Schedule {
+    fn is_active(&self) -> bool {
+        self.enabled && !self.windows.is_empty()
+    }
+
+    // Occurrences of all windows beginning on `dates`
+    fn occurrences<Tz: TimeZone>(
+        &self,
+        tz: &Tz,
+        dates: impl Iterator<Item = NaiveDate>,
+    ) -> Vec<(DateTime<Tz>, DateTime<Tz>)> {
+        dates
+            .flat_map(|date| self.windows.iter().filter_map(move |w| w.occurrence(tz, date)))
+            .collect()
+    }
+
+    // Whether the bot should be running at `at`
+    pub fn wants_running<Tz: TimeZone>(&self, at: DateTime<Tz>) -> bool {
+        let today = at.date_naive();
+        // Yesterday's overnight windows may still be open
+        self.occurrences(&at.timezone(), [today.pred_opt(), Some(today)].into_iter().flatten())
+            .into_iter()
+            .any(|(start, end)| start <= at && at < end)
+    }
+
+    // The first boundary after `after` at which the desired state changes
+    fn next_change<Tz: TimeZone>(&self, after: DateTime<Tz>) -> Option<(DateTime<Tz>, bool)> {
+        let today = after.date_naive();
+        let dates = (-1..LOOKAHEAD_DAYS)
+            .filter_map(|offset| today.checked_add_signed(ChronoDuration::days(offset)));
+        let mut boundaries: Vec<DateTime<Tz>> = self
+            .occurrences(&after.timezone(), dates)
+            .into_iter()
+            .flat_map(|(start, end)| [start, end])
+            .filter(|at| *at > after)
+            .collect();
+        boundaries.sort();
+
+        let current = self.wants_running(after);
+        boundaries
+            .into_iter()
+            .map(|at| (at.clone(), self.wants_running(at)))
+            .find(|(_, running)| *running != current)
+    }
+}
+
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledAction {
+    Start,
+    Stop,
+}
+
+impl ScheduledAction {
+    fn from_running(running: bool) -> Self {
+        if running {
+            ScheduledAction::Start
+        } else {
+            ScheduledAction::Stop
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Override {
+    running: bool,
+    // The next boundary, when the schedule takes over again
+    until: DateTime<Local>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ScheduleOverride {
+    running: bool,
+    until: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct NextScheduledAction {
+    profile_id: String,
+    action: ScheduledAction,
+    at: String,
+    #[serde(rename = "override")]
+    active_override: Option<ScheduleOverride>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ScheduleEvent {
+    profile_id: String,
+    action: ScheduledAction,
+}
+
+#[derive(Default)]
+struct ProfileState {
+    // Desired state seen at the last check; None before the first
+    applied: Option<bool>,
+    active_override: Option<Override>,
+}
+
+#[derive(Default)]
+pub struct Scheduler {
+    profiles: Mutex<HashMap<String, ProfileState>>,
+}
+
+// Whether auto-start should launch the profile right now
+pub fn allows_start(profile: &BotProfile) -> bool {
+    !profile.schedule.is_active() || profile.schedule.wants_running(Local::now())
+}
+
+// Bring the bot to the desired state through the controller
+async fn apply(app: &AppHandle, profile_id: &str, running: bool) {
+    let phase = lifecycle::current(&app.state::<BotState>().instance(profile_id));
+    // A bot in crash backoff is about to be relaunched; stopping it cancels that
+    let is_running = phase.is_running() || phase == BotPhase::Backoff;
+    if running == is_running {
+        return;
+    }
+
+    let action = ScheduledAction::from_running(running);
+    info!("Scheduler: {:?} bot [{}]", action, profile_id);
+    let controller: State<BotController> = app.state();
+    let result = match action {
+        ScheduledAction::Start => controller.start(profile_id).await.map(|launch| launch.message()),
+        ScheduledAction::Stop => controller.stop(profile_id, StopReason::Schedule).await.map(|report| report.message()),
+    };
+    match result {
+        Ok(msg) => {
+            let _ = app.emit("bot-status", msg);
+            let _ = app.emit("bot-scheduled-action", ScheduleEvent {
+                profile_id: profile_id.to_string(),
+                action,
+            });
+        }
+        Err(e) => {
+            error!("Scheduled {:?} of bot [{}] failed: {}", action, profile_id, e);
+            let _ = app.emit("bot-error", e);
+        }
+    }
+}
+
+// Desired state for `profile` if it changed since the last check
+fn due(scheduler: &Scheduler, profile: &BotProfile, now: DateTime<Local>) -> Option<bool> {
+    let mut states = scheduler.profiles.lock().ok()?;
+    let state = states.entry(profile.id.clone()).or_default();
+
+    if !profile.schedule.is_active() {
+        *state = ProfileState::default();
+        return None;
+    }
+    let running = profile.schedule.wants_running(now);
+    if let Some(active) = &state.active_override {
+        if now < active.until {
+            return None;
+        }
+        // The boundary passed; the schedule applies again
+        state.active_override = None;
+        state.applied = Some(running);
+        return Some(running);
+    }
+
+    match state.applied.replace(running) {
+        // First look at this profile: bring a bot reattached outside its window,
+        // or one inside it that doesn't auto-start, in line right away
+        None => Some(running),
+        Some(previous) if previous == running => None,
+        Some(_) => Some(running),
+    }
+}
+
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let now = Local::now();
+            for profile in profiles::load_all() {
+                let running = due(&app.state::<Scheduler>(), &profile, now);
+                if let Some(running) = running {
+                    apply(&app, &profile.id, running).await;
+                }
+            }
+            tokio::time::sleep(TICK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_next_scheduled_action(
+    scheduler: State<Scheduler>,
+    profile_id: Option<String>,
+) -> Result<Option<NextScheduledAction>, String> {
+    let profile = profiles::get(&profiles::resolve_id(profile_id))?;
+    if !profile.schedule.is_active() {
+        return Ok(None);
+    }
+    let active_override = scheduler
+        .profiles
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(&profile.id)
+        .and_then(|state| state.active_override.as_ref())
+        .map(|active| ScheduleOverride {
+            running: active.running,
+            until: active.until.to_rfc3339(),
+        });
+
+    Ok(profile.schedule.next_change(Local::now()).map(|(at, running)| NextScheduledAction {
+        profile_id: profile.id,
+        action: ScheduledAction::from_running(running),
+        at: at.to_rfc3339(),
+        active_override,
+    }))
+}
+
+// Keep the bot running (or stopped) regardless of the schedule until the next boundary
+#[tauri::command]
+pub async fn set_schedule_override(
+    app: AppHandle,
+    scheduler: State<'_, Scheduler>,
+    profile_id: Option<String>,
+    running: bool,
+) -> Result<(), String> {
+    let profile = profiles::get(&profiles::resolve_id(profile_id))?;
+    if !profile.schedule.is_active() {
+        return Err("Profile has no active schedule".to_string());
+    }
+    let (until, _) = profile
+        .schedule
+        .next_change(Local::now())
+        .ok_or("Schedule has no upcoming boundary")?;
+
+    {
+        let mut states = scheduler.profiles.lock().map_err(|e| e.to_string())?;
+        states.entry(profile.id.clone()).or_default().active_override =
+            Some(Override { running, until });
+    }
+    info!("Schedule override for bot [{}]: running={} until {}", profile.id, running, until);
+    apply(&app, &profile.id, running).await;
+    Ok(())
+}
+
+// End an override early and bring the bot back in line with the schedule
+#[tauri::command]
+pub async fn clear_schedule_override(
+    app: AppHandle,
+    scheduler: State<'_, Scheduler>,
+    profile_id: Option<String>,
+) -> Result<(), String> {
+    let profile = profiles::get(&profiles::resolve_id(profile_id))?;
+    let running = profile.schedule.wants_running(Local::now());
+    {
+        let mut states = scheduler.profiles.lock().map_err(|e| e.to_string())?;
+        let state = states.entry(profile.id.clone()).or_default();
+        if state.active_override.take().is_none() {
+            return Ok(());
+        }
+        state.applied = Some(running);
+    }
+    apply(&app, &profile.id, running).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Utc};
+
+    // Central European time with the 2026 DST changes, so the tests depend on
+    // neither the machine's zone nor its tzdata
+    #[derive(Clone, Copy, Debug)]
+    struct Berlin;
+
+    const WINTER: i32 = 3600;
+    const SUMMER: i32 = 7200;
+
+    impl Berlin {
+        fn offset_at(utc: &NaiveDateTime) -> FixedOffset {
+            // Both changes happen at 01:00 UTC
+            let change = |month, day| {
+                NaiveDate::from_ymd_opt(2026, month, day).unwrap().and_hms_opt(1, 0, 0).unwrap()
+            };
+            let summer = change(3, 29) <= *utc && *utc < change(10, 25);
+            FixedOffset::east_opt(if summer { SUMMER } else { WINTER }).unwrap()
+        }
+    }
+
+    impl TimeZone for Berlin {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Berlin
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        // Offsets under which `local` maps back to itself
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let fitting: Vec<FixedOffset> = [WINTER, SUMMER]
+                .into_iter()
+                .map(|seconds| FixedOffset::east_opt(seconds).unwrap())
+                .filter(|offset| Self::offset_at(&(*local - *offset)) == *offset)
+                .collect();
+            match fitting[..] {
+                [offset] => LocalResult::Single(offset),
+                // The later instant first, like `Local` on Linux
+                [winter, summer] => LocalResult::Ambiguous(winter, summer),
+                _ => LocalResult::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            Self::offset_at(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            Self::offset_at(utc)
+        }
+    }
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Berlin> {
+        Berlin.with_ymd_and_hms(year, month, day, hour, minute, 0).single().unwrap()
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    fn schedule(windows: &[(&[u32], &str, &str)]) -> Schedule {
+        Schedule {
+            enabled: true,
+            windows: windows
+                .iter()
+                .map(|(days, start, end)| RunWindow {
+                    days: days.to_vec(),
+                    start: start.to_string(),
+                    end: end.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    const WEEKDAYS: &[u32] = &[1, 2, 3, 4, 5];
+
+    #[test]
+    fn next_change_within_a_day() {
+        let office = schedule(&[(WEEKDAYS, "09:00", "17:00")]);
+        // Monday
+        assert_eq!(office.next_change(local(2026, 10, 12, 8, 0)), Some((local(2026, 10, 12, 9, 0), true)));
+        assert_eq!(office.next_change(local(2026, 10, 12, 10, 0)), Some((local(2026, 10, 12, 17, 0), false)));
+        // A boundary itself is already past
+        assert_eq!(office.next_change(local(2026, 10, 12, 9, 0)), Some((local(2026, 10, 12, 17, 0), false)));
+    }
+
+    #[test]
+    fn next_change_skips_days_without_windows() {
+        let office = schedule(&[(WEEKDAYS, "09:00", "17:00")]);
+        // Friday evening until Monday morning
+        assert_eq!(office.next_change(local(2026, 10, 16, 18, 0)), Some((local(2026, 10, 19, 9, 0), true)));
+    }
+
+    #[test]
+    fn overnight_window_ends_the_next_day() {
+        let nights = schedule(&[(&[5], "22:00", "06:00")]);
+        assert_eq!(nights.next_change(local(2026, 10, 16, 21, 0)), Some((local(2026, 10, 16, 22, 0), true)));
+        assert!(nights.wants_running(local(2026, 10, 16, 23, 0)));
+        assert_eq!(nights.next_change(local(2026, 10, 16, 23, 0)), Some((local(2026, 10, 17, 6, 0), false)));
+        // Saturday morning is still in Friday's window
+        assert!(nights.wants_running(local(2026, 10, 17, 3, 0)));
+        assert_eq!(nights.next_change(local(2026, 10, 17, 3, 0)), Some((local(2026, 10, 17, 6, 0), false)));
+        assert!(!nights.wants_running(local(2026, 10, 17, 6, 0)));
+    }
+
+    #[test]
+    fn touching_windows_are_one_run() {
+        let split = schedule(&[(&[1], "09:00", "12:00"), (&[1], "12:00", "15:00")]);
+        assert_eq!(split.next_change(local(2026, 10, 12, 10, 0)), Some((local(2026, 10, 12, 15, 0), false)));
+
+        let overnight = schedule(&[(&[1], "22:00", "06:00"), (&[2], "06:00", "08:00")]);
+        assert_eq!(overnight.next_change(local(2026, 10, 12, 23, 0)), Some((local(2026, 10, 13, 8, 0), false)));
+    }
+
+    #[test]
+    fn no_change_without_windows() {
+        assert_eq!(schedule(&[]).next_change(local(2026, 10, 12, 8, 0)), None);
+        // Always on
+        let always = schedule(&[(&[1, 2, 3, 4, 5, 6, 7], "00:00", "00:00")]);
+        assert_eq!(always.next_change(local(2026, 10, 12, 8, 0)), None);
+    }
+
+    #[test]
+    fn start_skipped_by_spring_forward_moves_past_the_gap() {
+        // 02:00 to 03:00 does not exist on 2026-03-29 in Berlin
+        let early = schedule(&[(&[7], "02:30", "05:00")]);
+        let (at, running) = early.next_change(local(2026, 3, 29, 1, 0)).unwrap();
+        assert_eq!(at, utc(2026, 3, 29, 1, 30));
+        assert!(running);
+        let (at, running) = early.next_change(at).unwrap();
+        assert_eq!(at, utc(2026, 3, 29, 3, 0));
+        assert!(!running);
+    }
+
+    #[test]
+    fn overnight_window_across_spring_forward_is_an_hour_shorter() {
+        let nights = schedule(&[(&[6], "22:00", "06:00")]);
+        let start = local(2026, 3, 28, 22, 0);
+        let (end, running) = nights.next_change(start).unwrap();
+        assert_eq!(end, local(2026, 3, 29, 6, 0));
+        assert!(!running);
+        assert_eq!(end - start, ChronoDuration::hours(7));
+    }
+
+    #[test]
+    fn repeated_hour_of_fall_back_is_covered() {
+        // 02:00 to 03:00 happens twice on 2026-10-25 in Berlin
+        let early = schedule(&[(&[7], "02:30", "03:00")]);
+        let (start, running) = early.next_change(local(2026, 10, 25, 0, 0)).unwrap();
+        // The first 02:30, still in summer time
+        assert_eq!(start, utc(2026, 10, 25, 0, 30));
+        assert!(running);
+        // The second 02:45 is still inside
+        assert!(early.wants_running(utc(2026, 10, 25, 1, 45).with_timezone(&Berlin)));
+        let (end, running) = early.next_change(start).unwrap();
+        // The only 03:00, in winter time
+        assert_eq!(end, utc(2026, 10, 25, 2, 0));
+        assert!(!running);
+
+        let late = schedule(&[(&[7], "03:00", "05:00")]);
+        let (start, running) = late.next_change(local(2026, 10, 25, 0, 0)).unwrap();
+        assert_eq!(start, utc(2026, 10, 25, 2, 0));
+        assert!(running);
+    }
+}
@@ -0,0 +1,170 @@
+// Secret storage for values injected into the bot's environment.
+//
+// Values live in the OS credential store: the login keychain through the
+// Security framework on macOS, the Secret Service through `secret-tool` on
+// Linux. Values never go through argv, where `ps` would show them.
+// Only the variable names are kept in ~/.chatcode/secrets.json so the
+// resolver knows what to look up; no secret value is written to disk by us.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+#[cfg(target_os = "linux")]
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+#[cfg(target_os = "macos")]
+use security_framework::passwords;
+
+use crate::{chatcode_dir, profiles};
+
+const SERVICE: &str = "ChatCode Desktop";
+
+fn account(profile_id: &str, key: &str) -> String {
+    format!("{}:{}", profile_id, key)
+}
+
+fn index_path() -> PathBuf {
+    chatcode_dir().join("secrets.json")
+}
+
+// Secret names per profile
+fn load_index() -> HashMap<String, Vec<String>> {
+    std::fs::read_to_string(index_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(index: &HashMap<String, Vec<String>>) -> Result<(), String> {
+    std::fs::create_dir_all(chatcode_dir())
+        .map_err(|e| format!("Failed to create .chatcode directory: {}", e))?;
+    let content = serde_json::to_string_pretty(index)
+        .map_err(|e| format!("Failed to serialize secret index: {}", e))?;
+    std::fs::write(index_path(), content).map_err(|e| format!("Failed to write secret index: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+fn run(command: &mut Command) -> Result<String, String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("Secret storage is not available: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn store(account: &str, value: &str) -> Result<(), String> {
+    // Replaces an existing item for the same account
+    passwords::set_generic_password(SERVICE, account, value.as_bytes()).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn lookup(account: &str) -> Result<String, String> {
+    let value = passwords::get_generic_password(SERVICE, account).map_err(|e| e.to_string())?;
+    String::from_utf8(value).map_err(|e| format!("Secret is not valid UTF-8: {}", e))
+}
+
+#[cfg(target_os = "macos")]
+fn erase(account: &str) -> Result<(), String> {
+    passwords::delete_generic_password(SERVICE, account).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn store(account: &str, value: &str) -> Result<(), String> {
+    // The value goes through stdin so it never shows up in the process list
+    let mut child = Command::new("secret-tool")
+        .args(["store", "--label", &format!("{} {}", SERVICE, account), "service", SERVICE, "account", account])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Secret storage is not available: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(value.as_bytes())
+            .map_err(|e| format!("Failed to pass secret to secret-tool: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to store secret: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn lookup(account: &str) -> Result<String, String> {
+    run(Command::new("secret-tool").args(["lookup", "service", SERVICE, "account", account]))
+}
+
+#[cfg(target_os = "linux")]
+fn erase(account: &str) -> Result<(), String> {
+    run(Command::new("secret-tool").args(["clear", "service", SERVICE, "account", account])).map(|_| ())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn store(_account: &str, _value: &str) -> Result<(), String> {
+    Err("Secret storage is not supported on this platform".to_string())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn lookup(_account: &str) -> Result<String, String> {
+    Err("Secret storage is not supported on this platform".to_string())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn erase(_account: &str) -> Result<(), String> {
+    Err("Secret storage is not supported on this platform".to_string())
+}
+
+// All secrets of a profile, read from the credential store
+pub fn load(profile_id: &str) -> Result<Vec<(String, String)>, String> {
+    let names = load_index().remove(profile_id).unwrap_or_default();
+    names
+        .into_iter()
+        .map(|key| {
+            let value = lookup(&account(profile_id, &key))
+                .map_err(|e| format!("Failed to read secret {}: {}", key, e))?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn list_secrets(profile_id: Option<String>) -> Vec<String> {
+    load_index()
+        .remove(&profiles::resolve_id(profile_id))
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn set_secret(profile_id: Option<String>, key: String, value: String) -> Result<(), String> {
+    let profile_id = profiles::resolve_id(profile_id);
+    if key.trim().is_empty() || key.contains('=') {
+        return Err("Secret name must be non-empty and must not contain '='".to_string());
+    }
+    store(&account(&profile_id, &key), &value)?;
+
+    let mut index = load_index();
+    let names = index.entry(profile_id).or_default();
+    if !names.contains(&key) {
+        names.push(key);
+    }
+    save_index(&index)
+}
+
+#[tauri::command]
+pub fn delete_secret(profile_id: Option<String>, key: String) -> Result<(), String> {
+    let profile_id = profiles::resolve_id(profile_id);
+    // Already gone from the store is fine; the index entry still has to go
+    let _ = erase(&account(&profile_id, &key));
+
+    let mut index = load_index();
+    if let Some(names) = index.get_mut(&profile_id) {
+        names.retain(|name| *name != key);
+    }
+    save_index(&index)
+}
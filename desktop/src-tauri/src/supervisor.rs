@@ -0,0 +1,344 @@
+// Crash supervisor for the bot process.
+//
+// Every launch gets a watcher thread that polls the child with `try_wait`.
+// When the bot exits on its own, the exit is recorded and the bot is
+// relaunched with exponential backoff, up to `max_restarts` per window.
+// Adopted bots are watched by PID; external ones are not relaunched.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+use log::{error, info, warn};
+
+use crate::envwatch::EnvReload;
+use crate::history::{self, StopReason};
+use crate::lifecycle::{self, BotPhase};
+use crate::controller::BotController;
+use crate::pidfile;
+use crate::resources;
+use crate::process::BotProcess;
+use crate::{chatcode_dir, send_notification, BotExit, BotInstance, BotState};
+
+// How often the watcher checks whether the child is still alive
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// How long to wait for the stderr reader to drain after the child exits
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+// How often the watcher folds the tree's memory into the run's peak
+const MEMORY_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SupervisorConfig {
+    pub auto_restart: bool,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub backoff_multiplier: f64,
+    pub max_restarts: u32,
+    pub restart_window_secs: u64,
+    // How long the bot gets to exit after SIGTERM before it is killed
+    pub stop_grace_period_ms: u64,
+    // How long a new bot gets to answer /health before the start counts as failed
+    pub startup_timeout_ms: u64,
+    // Restart a running bot whose /health stops answering
+    pub watchdog_enabled: bool,
+    pub watchdog_interval_ms: u64,
+    // A health check taking longer than this counts as a failure
+    pub watchdog_timeout_ms: u64,
+    // Consecutive failed checks before the bot is restarted
+    pub watchdog_failure_threshold: u32,
+    // What to do when a running bot's .env changes
+    pub env_reload: EnvReload,
+    // Also watch src/ of bots running in dev mode
+    pub watch_sources: bool,
+    // Quiet period after the last change before reacting to it
+    pub reload_debounce_ms: u64,
+    // Recent output lines kept in memory for `query_logs`, across profiles
+    pub log_buffer_lines: usize,
+    // bot.log is rotated past this size or age; 0 disables either limit
+    pub log_max_size_mb: u64,
+    pub log_max_age_hours: u64,
+    // Gzip rotated logs
+    pub log_compress: bool,
+    // Rotated logs kept, newest first
+    pub log_retention: usize,
+    // Per-run logs kept, newest first
+    pub run_log_retention: usize,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            auto_restart: true,
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 60_000,
+            backoff_multiplier: 2.0,
+            max_restarts: 5,
+            restart_window_secs: 300,
+            stop_grace_period_ms: 10_000,
+            startup_timeout_ms: 30_000,
+            watchdog_enabled: true,
+            watchdog_interval_ms: 15_000,
+            watchdog_timeout_ms: 5_000,
+            watchdog_failure_threshold: 4,
+            env_reload: EnvReload::Prompt,
+            watch_sources: false,
+            reload_debounce_ms: 2_000,
+            log_buffer_lines: 5_000,
+            log_max_size_mb: 50,
+            log_max_age_hours: 24,
+            log_compress: true,
+            log_retention: 10,
+            run_log_retention: 50,
+        }
+    }
+}
+
+impl SupervisorConfig {
+    fn path() -> PathBuf {
+        chatcode_dir().join("supervisor.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        std::fs::create_dir_all(chatcode_dir())
+            .map_err(|e| format!("Failed to create .chatcode directory: {}", e))?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize supervisor config: {}", e))?;
+        std::fs::write(Self::path(), content)
+            .map_err(|e| format!("Failed to write supervisor config: {}", e))
+    }
+
+    // Delay before the given restart attempt (0-based)
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self.backoff_multiplier.max(1.0).powi(attempt as i32);
+        let delay_ms = (self.initial_backoff_ms as f64 * factor).min(self.max_backoff_ms as f64);
+        Duration::from_millis(delay_ms as u64)
+    }
+}
+
+// Drop restarts that fell out of the window and count the rest
+fn recent_restarts(restarts: &mut VecDeque<Instant>, window: Duration) -> u32 {
+    while let Some(at) = restarts.front() {
+        if at.elapsed() > window {
+            restarts.pop_front();
+        } else {
+            break;
+        }
+    }
+    restarts.len() as u32
+}
+
+#[derive(Clone, Serialize)]
+pub struct RestartScheduled {
+    profile_id: String,
+    attempt: u32,
+    delay_ms: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct RestartsExhausted {
+    profile_id: String,
+    restarts: u32,
+    window_secs: u64,
+}
+
+// Spawn a watcher for the launch identified by `run_id`. The watcher quits
+// as soon as the process slot is emptied by a stop or taken over by a newer run.
+pub fn spawn_watcher(
+    app: AppHandle,
+    bot: Arc<BotInstance>,
+    run_id: u64,
+    stderr_reader: Option<JoinHandle<()>>,
+) {
+    thread::spawn(move || {
+        let mut last_sample = Instant::now();
+        let mut sample_pid = None;
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            // Measured outside the process lock; the tree walk reads all of /proc
+            if let Some(pid) = sample_pid.take() {
+                history::sample_memory(&bot, resources::tree_memory_mb(pid));
+            }
+            let (exit, external) = {
+                let mut process = match bot.process.lock() {
+                    Ok(process) => process,
+                    Err(_) => return,
+                };
+                if bot.run_id.load(Ordering::SeqCst) != run_id {
+                    return;
+                }
+                if last_sample.elapsed() >= MEMORY_SAMPLE_INTERVAL {
+                    last_sample = Instant::now();
+                    sample_pid = process.as_ref().map(|running| running.id());
+                }
+                let exit = match process.as_mut() {
+                    None => return,
+                    Some(BotProcess::Spawned(child)) => match child.try_wait() {
+                        Ok(Some(status)) => {
+                            // Descendants of a dead wrapper would keep holding the port
+                            #[cfg(unix)]
+                            crate::process::signal_group(child.id(), libc::SIGKILL);
+                            Some(status)
+                        }
+                        Ok(None) => continue,
+                        Err(e) => {
+                            error!("Failed to poll bot process: {}", e);
+                            continue;
+                        }
+                    },
+                    // Not our child, so there is no exit status to collect
+                    #[cfg(unix)]
+                    Some(BotProcess::Adopted { pid, .. }) if crate::process::pid_alive(*pid) => continue,
+                    Some(BotProcess::Adopted { .. }) => None,
+                };
+                // A reattached bot of ours crashed like a spawned one would have
+                let external = process.take().is_some_and(|p| p.is_external());
+                history::finish(&bot, if external { StopReason::External } else { StopReason::Crash }, exit);
+                pidfile::remove(&bot.profile_id);
+                let _ = lifecycle::transition(&app, &bot, BotPhase::Crashed);
+                (exit, external)
+            };
+
+            let uptime_seconds = bot
+                .start_time
+                .lock()
+                .ok()
+                .and_then(|mut start| start.take())
+                .map_or(0, |start| start.elapsed().as_secs());
+
+            // Let the reader pick up the final lines from the output file
+            if let Some(reader) = &stderr_reader {
+                let drain_start = Instant::now();
+                while !reader.is_finished() && drain_start.elapsed() < STDERR_DRAIN_TIMEOUT {
+                    thread::sleep(Duration::from_millis(50));
+                }
+            }
+            let stderr_tail = bot
+                .stderr_tail
+                .lock()
+                .map(|tail| tail.iter().cloned().collect())
+                .unwrap_or_default();
+
+            // External bots were started without our limits
+            let limit_exceeded = if external {
+                None
+            } else {
+                bot.limits
+                    .lock()
+                    .ok()
+                    .and_then(|limits| limits.exceeded(exit, &stderr_tail))
+            };
+            let record = BotExit::new(&bot.profile_id, exit, uptime_seconds, stderr_tail, limit_exceeded);
+            match exit {
+                Some(status) => warn!("Bot [{}] exited unexpectedly: {}", bot.profile_id, status),
+                None => warn!("Adopted bot [{}] exited", bot.profile_id),
+            }
+            if let Some(limit) = limit_exceeded {
+                warn!("Bot [{}] was killed by its {:?} limit", bot.profile_id, limit);
+                send_notification(&app, "ChatCode Bot", "Bot was killed for exceeding its resource limit");
+            }
+            let _ = app.emit("bot-exited", record.clone());
+            if let Ok(mut last_exit) = bot.last_exit.lock() {
+                *last_exit = Some(record);
+            }
+
+            // Whoever started an external bot may be about to start it again;
+            // relaunching our own copy would fight it for the Telegram token
+            if external {
+                let _ = lifecycle::transition(&app, &bot, BotPhase::Stopped);
+                return;
+            }
+            schedule_restart(&app, &bot, run_id);
+            return;
+        }
+    });
+}
+
+fn schedule_restart(app: &AppHandle, bot: &BotInstance, run_id: u64) {
+    let config = app.state::<BotState>().supervisor_config();
+    let attempt = {
+        let Ok(mut restarts) = bot.restarts.lock() else {
+            return;
+        };
+        recent_restarts(&mut restarts, Duration::from_secs(config.restart_window_secs))
+    };
+
+    if !config.auto_restart {
+        return;
+    }
+
+    if attempt >= config.max_restarts {
+        error!(
+            "Bot [{}] crashed {} times within {}s, giving up on automatic restarts",
+            bot.profile_id, attempt, config.restart_window_secs
+        );
+        let _ = app.emit("bot-restarts-exhausted", RestartsExhausted {
+            profile_id: bot.profile_id.clone(),
+            restarts: attempt,
+            window_secs: config.restart_window_secs,
+        });
+        send_notification(app, "ChatCode Bot", "Bot keeps crashing, automatic restart disabled");
+        return;
+    }
+
+    if lifecycle::transition(app, bot, BotPhase::Backoff).is_err() {
+        return;
+    }
+    let delay = config.backoff(attempt);
+    info!("Restarting bot [{}] in {}ms (attempt {})", bot.profile_id, delay.as_millis(), attempt + 1);
+    let _ = app.emit("bot-restart-scheduled", RestartScheduled {
+        profile_id: bot.profile_id.clone(),
+        attempt: attempt + 1,
+        delay_ms: delay.as_millis() as u64,
+    });
+    thread::sleep(delay);
+
+    // A manual start or stop during the backoff supersedes this restart
+    if bot.run_id.load(Ordering::SeqCst) != run_id {
+        info!("Automatic restart cancelled");
+        return;
+    }
+
+    if let Ok(mut restarts) = bot.restarts.lock() {
+        restarts.push_back(Instant::now());
+    }
+    // Runs on the watcher thread, so blocking on the controller is fine
+    let controller: State<BotController> = app.state();
+    match tauri::async_runtime::block_on(controller.start(&bot.profile_id)) {
+        Ok(launch) => {
+            let msg = launch.message();
+            let _ = app.emit("bot-status", msg);
+        }
+        Err(e) => {
+            error!("Automatic restart failed: {}", e);
+            let _ = app.emit("bot-error", e);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_supervisor_config(state: State<BotState>) -> SupervisorConfig {
+    state.supervisor_config()
+}
+
+#[tauri::command]
+pub fn set_supervisor_config(state: State<BotState>, config: SupervisorConfig) -> Result<(), String> {
+    config.save()?;
+    let mut supervisor = state.supervisor.lock().map_err(|e| e.to_string())?;
+    *supervisor = config;
+    Ok(())
+}
@@ -0,0 +1,154 @@
+// Watchdog for a bot that is alive but no longer serving.
+//
+// A wedged event loop keeps the process around, so the supervisor never sees
+// an exit. Each run gets a task that polls `/health`; after enough consecutive
+// failures or timeouts the bot is restarted through the controller. A bot
+// someone else started is only reported, as the supervisor won't fight whoever
+// runs it for the Telegram token either.
+
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use log::{error, info, warn};
+
+use crate::controller::BotController;
+use crate::history::StopReason;
+use crate::lifecycle::{self, BotPhase};
+use crate::{profiles, send_notification, BotInstance, BotState};
+
+// Payload of `bot-watchdog-restart`, and of `bot-watchdog-unresponsive` for an external bot
+#[derive(Clone, Serialize)]
+pub struct WatchdogRestart {
+    profile_id: String,
+    failures: u32,
+    last_error: String,
+}
+
+async fn check(client: &reqwest::Client, base_url: &str) -> Result<(), String> {
+    let response = client
+        .get(format!("{}/health", base_url))
+        .send()
+        .await
+        .map_err(|e| if e.is_timeout() { "timed out".to_string() } else { e.to_string() })?;
+    if !response.status().is_success() {
+        return Err(format!("status {}", response.status()));
+    }
+    Ok(())
+}
+
+// Watch the run `run_id` until it ends or is restarted by the watchdog
+pub fn spawn(app: AppHandle, bot: Arc<BotInstance>, run_id: u64) {
+    let Ok(profile) = profiles::get(&bot.profile_id) else {
+        return;
+    };
+    let base_url = profile.base_url();
+
+    tauri::async_runtime::spawn(async move {
+        let mut failures = 0;
+        // An external bot is reported once until it answers again
+        let mut reported = false;
+        loop {
+            let config = app.state::<BotState>().supervisor_config();
+            tokio::time::sleep(Duration::from_millis(config.watchdog_interval_ms)).await;
+
+            if bot.run_id.load(Ordering::SeqCst) != run_id {
+                return;
+            }
+            match lifecycle::current(&bot) {
+                BotPhase::Ready => {}
+                // Startup is covered by the readiness check
+                BotPhase::Starting => continue,
+                _ => return,
+            }
+            if !config.watchdog_enabled {
+                failures = 0;
+                continue;
+            }
+
+            let client = match reqwest::Client::builder()
+                .timeout(Duration::from_millis(config.watchdog_timeout_ms))
+                .build()
+            {
+                Ok(client) => client,
+                Err(e) => {
+                    error!("Watchdog could not create HTTP client: {}", e);
+                    return;
+                }
+            };
+            let last_error = match check(&client, &base_url).await {
+                Ok(()) => {
+                    failures = 0;
+                    reported = false;
+                    continue;
+                }
+                Err(e) => e,
+            };
+
+            failures += 1;
+            warn!(
+                "Bot [{}] health check failed ({}/{}): {}",
+                bot.profile_id, failures, config.watchdog_failure_threshold, last_error
+            );
+            if failures < config.watchdog_failure_threshold {
+                continue;
+            }
+            // A stop or restart may have happened while the probe was in flight
+            if bot.run_id.load(Ordering::SeqCst) != run_id {
+                return;
+            }
+
+            let external = bot
+                .process
+                .lock()
+                .is_ok_and(|process| process.as_ref().is_some_and(|process| process.is_external()));
+            if external {
+                if !reported {
+                    reported = true;
+                    warn!("External bot [{}] is unresponsive, leaving it to whoever started it", bot.profile_id);
+                    let _ = app.emit("bot-watchdog-unresponsive", WatchdogRestart {
+                        profile_id: bot.profile_id.clone(),
+                        failures,
+                        last_error: last_error.clone(),
+                    });
+                    send_notification(
+                        &app,
+                        "ChatCode Bot",
+                        &format!(
+                            "Bot stopped answering health checks ({} in a row, last: {}); it was started outside the app, so it is not restarted",
+                            failures, last_error
+                        ),
+                    );
+                }
+                continue;
+            }
+
+            error!("Bot [{}] is unresponsive, restarting", bot.profile_id);
+            let _ = app.emit("bot-watchdog-restart", WatchdogRestart {
+                profile_id: bot.profile_id.clone(),
+                failures,
+                last_error: last_error.clone(),
+            });
+            send_notification(
+                &app,
+                "ChatCode Bot",
+                &format!(
+                    "Bot stopped answering health checks ({} in a row, last: {}), restarting",
+                    failures, last_error
+                ),
+            );
+
+            // The new run gets its own watchdog
+            let controller: State<BotController> = app.state();
+            match controller.restart(&bot.profile_id, StopReason::Watchdog).await {
+                Ok(launch) => info!("Watchdog restarted bot [{}]: {}", bot.profile_id, launch.message()),
+                Err(e) => {
+                    error!("Watchdog restart failed: {}", e);
+                    let _ = app.emit("bot-error", e);
+                }
+            }
+            return;
+        }
+    });
+}
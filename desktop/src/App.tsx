@@ -22,6 +22,14 @@ interface Config {
   [key: string]: string;
 }
 
+// Payload of `bot-config-changed`
+interface ConfigChanged {
+  profile_id: string;
+  source: 'app' | 'external';
+  changed: string[];
+  restarting: boolean;
+}
+
 interface LogEntry {
   level: string;
   message: string;
@@ -49,6 +57,7 @@ function AppContent() {
   const [activeTab, setActiveTab] = useState<'status' | 'messages' | 'logs' | 'metrics' | 'users' | 'config'>('status');
   const [loading, setLoading] = useState(false);
   const [message, setMessage] = useState<{ type: 'success' | 'error'; text: string } | null>(null);
+  const [configChanged, setConfigChanged] = useState<ConfigChanged | null>(null); // Pending restart prompt
   const [logs, setLogs] = useState<LogEntry[]>([]);
   const logsEndRef = useRef<HTMLDivElement>(null);
   const [autostart, setAutostart] = useState<boolean>(false);
@@ -176,12 +185,17 @@ function AppContent() {
     const unlistenError = listen<string>('bot-error', (event) => {
       showMessage('error', event.payload);
     });
+    // The bot only reads .env at startup; offer a restart unless one is already under way
+    const unlistenConfig = listen<ConfigChanged>('bot-config-changed', (event) => {
+      setConfigChanged(event.payload.restarting ? null : event.payload);
+    });
 
     return () => {
       unlistenLogs.then((fn) => fn());
       unlistenSettings.then((fn) => fn());
       unlistenStatus.then((fn) => fn());
       unlistenError.then((fn) => fn());
+      unlistenConfig.then((fn) => fn());
     };
   }, []);
 
@@ -271,10 +285,10 @@ function AppContent() {
     setLoading(false);
   };
 
-  const restartBot = async () => {
+  const restartBot = async (profileId?: string) => {
     setLoading(true);
     try {
-      const result = await invoke<string>('restart_bot', { projectPath });
+      const result = await invoke<string>('restart_bot', { projectPath, profileId });
       showMessage('success', result);
     } catch (error) {
       showMessage('error', `${error}`);
@@ -365,6 +379,23 @@ function AppContent() {
       </header>
 
       {message && <div className={`message ${message.type}`}>{message.text}</div>}
+      {configChanged && (
+        <div className="message config-changed">
+          <span>{t('msg.configChanged')}</span>
+          <button
+            className="btn btn-small"
+            onClick={() => {
+              setConfigChanged(null);
+              restartBot(configChanged.profile_id);
+            }}
+          >
+            {t('control.restart')}
+          </button>
+          <button className="btn btn-small btn-secondary" onClick={() => setConfigChanged(null)}>
+            {t('msg.later')}
+          </button>
+        </div>
+      )}
 
       {activeTab === 'status' && (
         <div className="status-panel">
@@ -393,7 +424,7 @@ function AppContent() {
           <div className="controls">
             {status?.is_running ? (
               <>
-                <button className="btn btn-secondary" onClick={restartBot} disabled={loading}>
+                <button className="btn btn-secondary" onClick={() => restartBot()} disabled={loading}>
                   {loading ? t('control.restarting') : t('control.restart')}
                 </button>
                 <button className="btn btn-danger" onClick={stopBot} disabled={loading}>
@@ -67,6 +67,8 @@ export const en = {
   'msg.autostartDisabled': 'Auto-start disabled',
   'msg.failedAutostart': 'Failed to set auto-start',
   'msg.failedLoadConfig': 'Failed to load config',
+  'msg.configChanged': 'Configuration changed. Restart the bot to apply it.',
+  'msg.later': 'Later',
 
   // Message Simulator
   'messages.title': 'Messages',
@@ -69,6 +69,8 @@ export const zh: Record<TranslationKey, string> = {
   'msg.autostartDisabled': '自启动已关闭',
   'msg.failedAutostart': '设置自启动失败',
   'msg.failedLoadConfig': '加载配置失败',
+  'msg.configChanged': '配置已更改，重启机器人后生效。',
+  'msg.later': '稍后',
 
   // Message Simulator
   'messages.title': '消息',
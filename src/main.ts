@@ -77,11 +77,12 @@ async function main(): Promise<void> {
 
     console.log('Telegram handler initialized with callback architecture');
 
-    // Always start metrics server (port 3002) for dashboard integration
-    const metricsServer = new ExpressServer(bot, 3002, storage);
+    // Always start metrics server (port 3002 unless METRICS_PORT is set) for dashboard integration
+    const metricsPort = parseInt(process.env.METRICS_PORT || '3002', 10);
+    const metricsServer = new ExpressServer(bot, metricsPort, storage);
     metricsServer.setupRoutes();
     await metricsServer.start();
-    console.log('Metrics server started on port 3002');
+    console.log(`Metrics server started on port ${metricsPort}`);
 
     if (config.telegram.mode === 'webhook') {
       if (!config.webhook) {